@@ -0,0 +1,378 @@
+use crate::{derive, AddressFormat, Derivation, Error, Incremental, SplitKey};
+use bip39::{Language, Mnemonic, MnemonicType};
+use rand::RngCore;
+use regex::Regex;
+use schnorrkel::SecretKey;
+use sp_core::{crypto::AccountId32, ecdsa, ed25519, hashing::blake2_256, sr25519, Pair};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Signature scheme, which is used to derive account from seed
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scheme {
+    #[default]
+    Sr25519,
+    Ed25519,
+    /// secp256k1, account id is blake2-256 of compressed public key
+    Ecdsa,
+}
+
+impl Scheme {
+    /// All supported schemes
+    pub const ALL: &'static [Scheme] = &[Scheme::Sr25519, Scheme::Ed25519, Scheme::Ecdsa];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scheme::Sr25519 => "sr25519",
+            Scheme::Ed25519 => "ed25519",
+            Scheme::Ecdsa => "ecdsa",
+        }
+    }
+
+    /// Public key of pair, created from seed
+    pub fn public(self, seed: &[u8; 32]) -> Vec<u8> {
+        match self {
+            Scheme::Sr25519 => sr25519::Pair::from_seed(seed).public().0.to_vec(),
+            Scheme::Ed25519 => ed25519::Pair::from_seed(seed).public().0.to_vec(),
+            Scheme::Ecdsa => ecdsa::Pair::from_seed(seed).public().0.to_vec(),
+        }
+    }
+
+    /// Derive account id from public key, the same way `subkey` does
+    pub fn account_id(self, public: &[u8]) -> AccountId32 {
+        match self {
+            Scheme::Sr25519 | Scheme::Ed25519 => {
+                AccountId32::try_from(public).expect("public key is 32 bytes long")
+            }
+            // Same as `MultiSigner::Ecdsa(..).into_account()`
+            Scheme::Ecdsa => blake2_256(public).into(),
+        }
+    }
+
+    /// Convert BIP39 entropy to seed, the same way `Pair::from_phrase` does
+    pub fn seed_from_entropy(self, entropy: &[u8]) -> [u8; 32] {
+        match self {
+            Scheme::Sr25519 => substrate_bip39::mini_secret_from_entropy(entropy, "")
+                .expect("entropy of valid mnemonic is always accepted")
+                .to_bytes(),
+            Scheme::Ed25519 | Scheme::Ecdsa => {
+                let big_seed = substrate_bip39::seed_from_entropy(entropy, "")
+                    .expect("entropy of valid mnemonic is always accepted");
+                let mut seed = [0; 32];
+                seed.copy_from_slice(&big_seed[..32]);
+                seed
+            }
+        }
+    }
+}
+impl Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scheme::ALL
+            .iter()
+            .copied()
+            .find(|scheme| scheme.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown scheme: {}, expected sr25519, ed25519 or ecdsa", s))
+    }
+}
+
+/// How candidate seeds are produced
+#[derive(Default, Clone)]
+pub enum SeedSource {
+    /// Random 32 byte seed
+    #[default]
+    Raw,
+    /// Random BIP39 phrase, seed is derived from its entropy
+    ///
+    /// Much slower than raw seeds, as every attempt runs PBKDF2.
+    /// Note that most substrate wallets only accept english phrases
+    Mnemonic(MnemonicType, Language),
+    /// Derive accounts from a single secret, enumerating derivation paths
+    Derive(Arc<Derivation>),
+    /// Search for offset to someone else's public key
+    SplitKey(SplitKey),
+    /// Step secret scalar by one for every attempt, instead of generating a new key
+    ///
+    /// Found accounts only have expanded secret key, not seed, and secrets
+    /// found by the same worker batch are related
+    Incremental,
+}
+
+/// Generated account, together with secret needed to import it
+#[derive(Clone)]
+pub struct Account {
+    pub scheme: Scheme,
+    pub format: AddressFormat,
+    pub public: Vec<u8>,
+    /// Missing if account was derived using soft junctions
+    pub seed: Option<[u8; 32]>,
+    /// BIP39 phrase, from which seed was derived
+    pub phrase: Option<String>,
+    /// Derivation path, which should be applied to the base secret
+    pub path: Option<String>,
+    /// Split-key offset, which should be added to the requester's secret
+    pub offset: Option<[u8; 32]>,
+    /// sr25519 expanded secret key (scalar and nonce), as accepted by `Pair::from_seed_slice`
+    ///
+    /// Set for accounts which have no seed (mini-secret)
+    pub secret_key: Option<[u8; 64]>,
+    /// Empty for candidates, which weren't yet checked by [`crate::Matcher`]
+    pub address: String,
+    /// Addresses of the same key in other requested network formats
+    pub other_addresses: Vec<(AddressFormat, String)>,
+    /// Part of address, matched by pattern with several variants, i.e case-insensitive
+    pub matched: Option<String>,
+    /// Name of the pattern, which address has matched, see [`crate::Pattern`]
+    pub pattern: Option<String>,
+    /// Regexes, which addresses have matched, with their formats
+    ///
+    /// Regexes which match any address, i.e when only wordlist is used, are omitted
+    pub regexes: Vec<(AddressFormat, String)>,
+    /// Word, found in address, and its character index, see [`crate::Wordlist`]
+    pub word: Option<(String, usize)>,
+}
+
+impl Account {
+    /// Candidate without any secret attached, address is not encoded yet
+    pub(crate) fn unencoded(scheme: Scheme, format: AddressFormat, public: Vec<u8>) -> Account {
+        Self {
+            scheme,
+            format,
+            public,
+            seed: None,
+            phrase: None,
+            path: None,
+            offset: None,
+            secret_key: None,
+            address: String::new(),
+            other_addresses: Vec::new(),
+            matched: None,
+            pattern: None,
+            regexes: Vec::new(),
+            word: None,
+        }
+    }
+    fn unencoded_from_seed(scheme: Scheme, format: AddressFormat, seed: [u8; 32]) -> Account {
+        Self {
+            seed: Some(seed),
+            ..Self::unencoded(scheme, format, scheme.public(&seed))
+        }
+    }
+    /// Account without any secret attached
+    pub fn from_public(scheme: Scheme, format: AddressFormat, public: Vec<u8>) -> Account {
+        Self::unencoded(scheme, format, public).encoded()
+    }
+    pub fn from_seed(scheme: Scheme, format: AddressFormat, seed: [u8; 32]) -> Account {
+        Self::unencoded_from_seed(scheme, format, seed).encoded()
+    }
+
+    /// Account for secret in any form printed by search: seed or secret key hex, or phrase
+    ///
+    /// Secret may be followed by derivation path and password, as accepted by `subkey`,
+    /// i.e `phrase//hard/soft///password`. Phrases without path may be in any `language`
+    pub fn from_secret(
+        scheme: Scheme,
+        format: AddressFormat,
+        secret: &str,
+        language: Language,
+    ) -> Result<Account, Error> {
+        let secret = secret.trim();
+        let (base, path) = secret.split_at(secret.find('/').unwrap_or(secret.len()));
+        if !path.is_empty() {
+            let (public, seed) = derive::from_suri(scheme, secret)?;
+            return Ok(Self {
+                seed,
+                path: Some(path.to_owned()),
+                ..Self::from_public(scheme, format, public)
+            });
+        }
+        // Ethereum private keys are printed without 0x
+        if let Ok(bytes) = hex::decode(base.trim_start_matches("0x")) {
+            return match (scheme, bytes.len()) {
+                (_, 32) => Ok(Self::from_seed(
+                    scheme,
+                    format,
+                    bytes.try_into().expect("length was checked"),
+                )),
+                (Scheme::Sr25519, 64) => {
+                    let secret_key: [u8; 64] = bytes.try_into().expect("length was checked");
+                    let public = SecretKey::from_bytes(&secret_key)
+                        .map_err(|_| Error::InvalidSecret("secret key is malformed"))?
+                        .to_public();
+                    Ok(Self {
+                        secret_key: Some(secret_key),
+                        ..Self::from_public(scheme, format, public.to_bytes().to_vec())
+                    })
+                }
+                _ => Err(Error::InvalidSecret(
+                    "expected 32 byte seed, or 64 byte sr25519 secret key",
+                )),
+            };
+        }
+        let mnemonic = Mnemonic::from_phrase(base, language)
+            .map_err(|_| Error::InvalidSecret("phrase is not a valid BIP39 mnemonic"))?;
+        Ok(Self {
+            phrase: Some(mnemonic.phrase().to_owned()),
+            ..Self::from_seed(scheme, format, scheme.seed_from_entropy(mnemonic.entropy()))
+        })
+    }
+
+    /// Encode address, if it wasn't encoded yet
+    pub fn encode(&mut self) {
+        if self.address.is_empty() {
+            self.address = self.format.address(self.scheme, &self.public);
+        }
+    }
+    pub(crate) fn encoded(mut self) -> Self {
+        self.encode();
+        self
+    }
+
+    /// Substrate account id, which is encoded in ss58 address: public key, or its blake2-256
+    /// hash for ecdsa. H160 address encodes another hash, see [`AddressFormat::account_id`]
+    pub fn account_id(&self) -> AccountId32 {
+        self.scheme.account_id(&self.public)
+    }
+
+    /// Generate random account, and encode its address in specified network format
+    pub fn generate<R: RngCore>(
+        rng: &mut R,
+        scheme: Scheme,
+        source: &SeedSource,
+        format: AddressFormat,
+    ) -> Account {
+        Self::candidate(rng, scheme, source, format).encoded()
+    }
+
+    /// Same as [`Account::generate`], but address is left for matcher to encode
+    pub(crate) fn candidate<R: RngCore>(
+        rng: &mut R,
+        scheme: Scheme,
+        source: &SeedSource,
+        format: AddressFormat,
+    ) -> Account {
+        match source {
+            SeedSource::Raw => {
+                let mut seed = [0; 32];
+                rng.fill_bytes(&mut seed);
+                Self::unencoded_from_seed(scheme, format, seed)
+            }
+            SeedSource::Mnemonic(words, language) => {
+                let mut entropy = [0; 32];
+                let entropy = &mut entropy[..words.entropy_bits() / 8];
+                rng.fill_bytes(entropy);
+                let mnemonic =
+                    Mnemonic::from_entropy(entropy, *language).expect("entropy has correct length");
+                Self {
+                    phrase: Some(mnemonic.into_phrase()),
+                    ..Self::unencoded_from_seed(scheme, format, scheme.seed_from_entropy(entropy))
+                }
+            }
+            SeedSource::Derive(derivation) => derivation.next(format),
+            SeedSource::SplitKey(split) => split.generate(rng, format),
+            SeedSource::Incremental => {
+                let mut accounts = Vec::with_capacity(1);
+                Incremental::default().fill(rng, &mut accounts, 1, format);
+                accounts.pop().expect("one account was generated")
+            }
+        }
+    }
+
+    /// Check encoded address against pattern
+    ///
+    /// H160 addresses are matched both in EIP-55 and lowercase forms
+    pub fn is_match(&self, regex: &Regex) -> bool {
+        address_matches(self.format, &self.address, regex)
+    }
+
+    /// Address in specified network format, encoded on first use
+    pub fn address_in(&mut self, format: AddressFormat) -> &str {
+        if format == self.format {
+            self.encode();
+            return &self.address;
+        }
+        let index = match self.other_addresses.iter().position(|(f, _)| *f == format) {
+            Some(index) => index,
+            None => {
+                let address = format.address(self.scheme, &self.public);
+                self.other_addresses.push((format, address));
+                self.other_addresses.len() - 1
+            }
+        };
+        &self.other_addresses[index].1
+    }
+    pub(crate) fn is_match_in(&mut self, format: AddressFormat, regex: &Regex) -> bool {
+        address_matches(format, self.address_in(format), regex)
+    }
+}
+
+pub(crate) fn address_matches(format: AddressFormat, address: &str, regex: &Regex) -> bool {
+    regex.is_match(address)
+        || (format == AddressFormat::H160 && regex.is_match(&address.to_lowercase()))
+}
+impl Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.other_addresses.is_empty() {
+            write!(f, "{}, {}", self.address, self.scheme)?;
+        } else {
+            write!(f, "{} ({})", self.address, self.format)?;
+            for (format, address) in &self.other_addresses {
+                write!(f, ", {} ({})", address, format)?;
+            }
+            write!(f, ", {}", self.scheme)?;
+        }
+        let mut separator = " ";
+        if let Some(seed) = &self.seed {
+            if self.format == AddressFormat::H160 {
+                // In form accepted by MetaMask import
+                write!(f, " private key = {}", hex::encode(seed))?;
+            } else {
+                write!(f, " seed = 0x{}", hex::encode(seed))?;
+            }
+            separator = ", ";
+        }
+        if let Some(secret_key) = &self.secret_key {
+            write!(f, "{}secret key = 0x{}", separator, hex::encode(secret_key))?;
+            separator = ", ";
+        }
+        if let Some(offset) = &self.offset {
+            write!(f, "{}offset = 0x{}", separator, hex::encode(offset))?;
+            separator = ", ";
+        }
+        if let Some(path) = &self.path {
+            write!(f, "{}path = {}", separator, path)?;
+            separator = ", ";
+        }
+        if let Some(phrase) = &self.phrase {
+            write!(f, "{}phrase = \"{}\"", separator, phrase)?;
+            separator = ", ";
+        }
+        if let Some(matched) = &self.matched {
+            write!(f, "{}matched = \"{}\"", separator, matched)?;
+            separator = ", ";
+        }
+        if let Some(pattern) = &self.pattern {
+            write!(f, "{}pattern = {}", separator, pattern)?;
+            separator = ", ";
+        }
+        for (format, regex) in &self.regexes {
+            if self.other_addresses.is_empty() {
+                write!(f, "{}regex = \"{}\"", separator, regex)?;
+            } else {
+                write!(f, "{}regex ({}) = \"{}\"", separator, format, regex)?;
+            }
+            separator = ", ";
+        }
+        if let Some((word, position)) = &self.word {
+            write!(f, "{}word = \"{}\" at {}", separator, word, position)?;
+        }
+        Ok(())
+    }
+}
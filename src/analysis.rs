@@ -0,0 +1,448 @@
+use crate::{format::ss58_prefix, matcher::ALPHABET, AddressFormat, Scheme};
+use regex_automata::{
+    dfa::{dense, Automaton, StartKind},
+    util::{primitives::StateID, start},
+    Anchored,
+};
+use regex_syntax::hir::{Hir, HirKind};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+
+/// Patterns, for which DFA is larger than this, are not analysed
+const DFA_SIZE_LIMIT: usize = 16 << 20;
+/// Length of strings, against which pattern is checked regardless of format
+const ANY_LENGTH: RangeInclusive<usize> = 1..=128;
+/// Bits of account id and checksum in ss58 address
+const BODY_BITS: usize = 8 * (32 + 2);
+/// Leading characters, which have exactly computed distribution
+const HEAD_CHARS: usize = 3;
+
+/// Pattern compiled to DFA, which is walked over characters possible in addresses
+pub(crate) struct Analysis {
+    dfa: dense::DFA<Vec<u32>>,
+    start: StateID,
+}
+
+impl Analysis {
+    /// `None` if DFA is too large, or pattern uses features unsupported by DFA
+    pub(crate) fn new(pattern: &str) -> Option<Self> {
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .start_kind(StartKind::Unanchored)
+                    .dfa_size_limit(Some(DFA_SIZE_LIMIT))
+                    .determinize_size_limit(Some(DFA_SIZE_LIMIT)),
+            )
+            .build(pattern)
+            .ok()?;
+        let start = dfa
+            .start_state(&start::Config::new().anchored(Anchored::No))
+            .ok()?;
+        Some(Self { dfa, start })
+    }
+
+    /// Whether pattern matches any string of `lengths`, consisting of `chars(position)`
+    fn may_match<'a>(
+        &self,
+        chars: impl Fn(usize) -> &'a [u8],
+        lengths: RangeInclusive<usize>,
+    ) -> bool {
+        let mut states = HashSet::from([self.start]);
+        for position in 0..=*lengths.end() {
+            if lengths.contains(&position)
+                && states
+                    .iter()
+                    .any(|s| self.dfa.is_match_state(self.dfa.next_eoi_state(*s)))
+            {
+                return true;
+            }
+            let mut next = HashSet::new();
+            for state in &states {
+                for c in chars(position) {
+                    let state = self.dfa.next_state(*state, *c);
+                    // Match is reported one byte late, so it has ended before this position
+                    if self.dfa.is_match_state(state) {
+                        return true;
+                    }
+                    if !self.dfa.is_dead_state(state) && !self.dfa.is_quit_state(state) {
+                        next.insert(state);
+                    }
+                }
+            }
+            if next.is_empty() {
+                return false;
+            }
+            states = next;
+        }
+        false
+    }
+
+    /// Probability of pattern matching random string, starting with one of `heads`,
+    /// followed by independent characters of specified probabilities
+    fn probability(&self, heads: &[Head], chars: &[(u8, f64)]) -> f64 {
+        let mut matched = 0.0;
+        // Distribution of states after reading head, by address length and head length
+        let mut walks: HashMap<(usize, usize), HashMap<StateID, f64>> = HashMap::new();
+        'heads: for (len, head, p) in heads {
+            let mut state = self.start;
+            for c in head {
+                state = self.dfa.next_state(state, *c);
+                if self.dfa.is_match_state(state) {
+                    matched += p;
+                    continue 'heads;
+                }
+                if self.dfa.is_dead_state(state) || self.dfa.is_quit_state(state) {
+                    continue 'heads;
+                }
+            }
+            *walks
+                .entry((*len, head.len()))
+                .or_default()
+                .entry(state)
+                .or_default() += p;
+        }
+        for ((len, position), mut states) in walks {
+            for _ in position..len {
+                let mut next: HashMap<StateID, f64> = HashMap::new();
+                for (state, p) in &states {
+                    for (c, weight) in chars {
+                        let state = self.dfa.next_state(*state, *c);
+                        if self.dfa.is_match_state(state) {
+                            matched += p * weight;
+                        } else if !self.dfa.is_dead_state(state) && !self.dfa.is_quit_state(state) {
+                            *next.entry(state).or_default() += p * weight;
+                        }
+                    }
+                }
+                states = next;
+            }
+            for (state, p) in states {
+                if self.dfa.is_match_state(self.dfa.next_eoi_state(state)) {
+                    matched += p;
+                }
+            }
+        }
+        matched
+    }
+}
+
+/// Probability of random address to match pattern
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    probability: f64,
+}
+
+impl Estimate {
+    pub fn probability(self) -> f64 {
+        self.probability
+    }
+    /// Expected number of attempts per match
+    pub fn attempts(self) -> f64 {
+        1.0 / self.probability
+    }
+    /// Match is found once in 2^bits attempts
+    pub fn bits(self) -> f64 {
+        -self.probability.log2()
+    }
+    /// Number of attempts, after which at least one match is found with `confidence` probability
+    pub fn attempts_for(self, confidence: f64) -> f64 {
+        (1.0 - confidence).ln() / (-self.probability).ln_1p()
+    }
+    /// Expected time to find `matches` more at `rate` attempts per second, in seconds
+    pub fn eta(self, matches: usize, rate: u64) -> Option<f64> {
+        (matches > 0 && rate > 0).then(|| self.attempts() * matches as f64 / rate as f64)
+    }
+    /// Every pattern should match, patterns are assumed to be independent
+    pub fn all(estimates: impl IntoIterator<Item = Estimate>) -> Estimate {
+        Estimate {
+            probability: estimates.into_iter().map(|e| e.probability).product(),
+        }
+    }
+    /// Every pattern should match, while their prefixes constrain the same account id
+    ///
+    /// Estimates come with the share of keys, allowed by prefix of their pattern, and `common`
+    /// is the share allowed by all prefixes at once. The rest of patterns are assumed to be
+    /// independent
+    pub(crate) fn all_within(
+        estimates: impl IntoIterator<Item = (Estimate, f64)>,
+        common: f64,
+    ) -> Estimate {
+        let rest: f64 = estimates
+            .into_iter()
+            .map(|(e, share)| (e.probability / share).min(1.0))
+            .product();
+        Estimate {
+            probability: common * rest,
+        }
+    }
+    /// At least one pattern should match, patterns are assumed to be independent
+    pub fn any(estimates: impl IntoIterator<Item = Estimate>) -> Estimate {
+        // Logarithm of probability to miss all, keeps precision for rare patterns
+        let miss: f64 = estimates
+            .into_iter()
+            .map(|e| (-e.probability).ln_1p())
+            .sum();
+        Estimate {
+            probability: -miss.exp_m1(),
+        }
+    }
+}
+impl Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "1 in 2^{:.1}", self.bits())
+    }
+}
+
+/// Estimate, how often addresses of keys of `scheme` match pattern
+///
+/// Distribution of leading characters and address length is computed from the format prefix,
+/// the rest of characters, including checksum tail, are assumed to be uniformly random.
+/// `None` if pattern can't be analysed.
+pub fn estimate(pattern: &str, format: AddressFormat, scheme: Scheme) -> Option<Estimate> {
+    let analysis = Analysis::new(pattern)?;
+    let probability = match format {
+        AddressFormat::Ss58(format) => {
+            let alphabet = ALPHABET.map(|c| (c, 1.0 / 58.0));
+            analysis.probability(&ss58_heads(format, scheme), &alphabet)
+        }
+        AddressFormat::H160 => {
+            // EIP-55 makes each letter uppercase with 1/2 probability
+            let mut checksummed = Vec::new();
+            for c in b"0123456789abcdef" {
+                if c.is_ascii_digit() {
+                    checksummed.push((*c, 1.0 / 16.0));
+                } else {
+                    checksummed.push((*c, 1.0 / 32.0));
+                    checksummed.push((c.to_ascii_uppercase(), 1.0 / 32.0));
+                }
+            }
+            let lowercase = b"0123456789abcdef".map(|c| (c, 1.0 / 16.0));
+            let heads = [(42, b"0x".to_vec(), 1.0)];
+            // Both forms are matched, estimate is a lower bound
+            analysis
+                .probability(&heads, &checksummed)
+                .max(analysis.probability(&heads, &lowercase))
+        }
+    };
+    Some(Estimate { probability })
+}
+
+/// Estimate, how often address contains any of `words` after its leading characters
+///
+/// Occurrences at different positions are assumed to be independent.
+pub(crate) fn estimate_words(
+    words: &[String],
+    format: AddressFormat,
+    ignore_case: bool,
+) -> Estimate {
+    let (alphabet, len): (&[u8], usize) = match format {
+        AddressFormat::Ss58(_) => (ALPHABET, *format.address_len().end()),
+        // Lowercase form is matched too, so case never matters
+        AddressFormat::H160 => (b"0123456789abcdef", 42),
+    };
+    let char_probability = |c: u8| {
+        let variants = if ignore_case || format == AddressFormat::H160 {
+            alphabet
+                .iter()
+                .filter(|a| a.eq_ignore_ascii_case(&c))
+                .count()
+        } else {
+            alphabet.iter().filter(|a| **a == c).count()
+        };
+        variants as f64 / alphabet.len() as f64
+    };
+    let positions = words.iter().flat_map(|word| {
+        let probability = word.bytes().map(char_probability).product::<f64>();
+        let positions = (len - format.fixed_chars() + 1).saturating_sub(word.len());
+        std::iter::repeat_n(Estimate { probability }, positions)
+    });
+    Estimate::any(positions)
+}
+
+/// Address length, leading characters and their probability
+type Head = (usize, Vec<u8>, f64);
+
+/// Distribution of leading characters, determined by format prefix
+///
+/// Values are in units of 2^BODY_BITS, so format prefix is the integer part,
+/// and account id with checksum is the fractional one.
+fn ss58_heads(format: u16, scheme: Scheme) -> Vec<Head> {
+    let prefix = ss58_prefix(format);
+    let max_len = *AddressFormat::Ss58(format).address_len().end();
+    let unit = 2f64.powi(BODY_BITS as i32);
+    let mut heads = Vec::new();
+    let (ones, base, lo) = if prefix[0] == 0 {
+        // Account id with zero first byte adds another '1', the rest is assumed random
+        heads.push((max_len, b"11".to_vec(), measure(0.0, 1.0 / 256.0, scheme)));
+        (1, 0.0, 1.0 / 256.0)
+    } else {
+        let base = prefix.iter().fold(0.0, |acc, b| acc * 256.0 + *b as f64);
+        (0, base, base)
+    };
+    let hi = base + 1.0;
+
+    for digits in 1.. {
+        let scale = 58f64.powi(digits - 1) / unit;
+        if scale >= hi {
+            break;
+        }
+        // Intervals of values, which start with the same characters
+        let mut intervals = vec![(Vec::new(), 0.0, scale * 58.0)];
+        for _ in 0..HEAD_CHARS.min(digits as usize) {
+            let mut next = Vec::new();
+            for (chars, start, end) in intervals {
+                let step = (end - start) / 58.0;
+                // Leading zero digit would make value shorter
+                let first = if chars.is_empty() { 1 } else { 0 };
+                for (digit, c) in ALPHABET.iter().enumerate().skip(first) {
+                    let (from, to) = (
+                        start + digit as f64 * step,
+                        start + (digit + 1) as f64 * step,
+                    );
+                    if to > lo && from < hi {
+                        let mut chars = chars.clone();
+                        chars.push(*c);
+                        next.push((chars, from, to));
+                    }
+                }
+            }
+            intervals = next;
+        }
+        for (chars, from, to) in intervals {
+            let mut head = vec![b'1'; ones];
+            head.extend(chars);
+            heads.push((
+                ones + digits as usize,
+                head,
+                measure(from.max(lo) - base, to.min(hi) - base, scheme),
+            ));
+        }
+    }
+    let total: f64 = heads.iter().map(|(_, _, p)| p).sum();
+    for (_, _, p) in &mut heads {
+        *p /= total;
+    }
+    heads
+}
+
+/// Share of keys, account id (and checksum) of which are within `from..to`, scaled to `0..1`
+pub(crate) fn measure(from: f64, to: f64, scheme: Scheme) -> f64 {
+    if scheme != Scheme::Sr25519 {
+        return to - from;
+    }
+    // Ristretto encoding always has even first byte, odd ones are never produced
+    let mut share = 0.0;
+    for byte in (from * 256.0) as u32..(to * 256.0).ceil() as u32 {
+        if byte % 2 == 0 {
+            let (lo, hi) = (byte as f64 / 256.0, (byte + 1) as f64 / 256.0);
+            share += 2.0 * (to.min(hi) - from.max(lo)).max(0.0);
+        }
+    }
+    share
+}
+
+/// Explain, why pattern can't match any address of `format`
+pub(crate) fn check_reachable(pattern: &str, format: AddressFormat) -> Result<(), String> {
+    let Some(analysis) = Analysis::new(pattern) else {
+        return Ok(());
+    };
+    let (alphabet, first): (Vec<u8>, Vec<u8>) = match format {
+        AddressFormat::Ss58(_) => (
+            ALPHABET.to_vec(),
+            format.leading_chars().iter().map(|c| *c as u8).collect(),
+        ),
+        AddressFormat::H160 => (b"0123456789abcdefABCDEF".to_vec(), b"0".to_vec()),
+    };
+    let chars = |position| match (format, position) {
+        (_, 0) => &first[..],
+        (AddressFormat::H160, 1) => b"x",
+        _ => &alphabet[..],
+    };
+
+    // Any character, which may appear in address
+    let mut any = alphabet.clone();
+    if format == AddressFormat::H160 {
+        any.push(b'x');
+    }
+    if !analysis.may_match(|_| &any[..], ANY_LENGTH) {
+        let hir = regex_syntax::parse(pattern).map_err(|e| e.to_string())?;
+        let mut invalid = Vec::new();
+        literal_chars(&hir, &mut invalid);
+        invalid.retain(|c| !any.contains(c));
+        invalid.sort_unstable();
+        invalid.dedup();
+        if invalid.is_empty() {
+            return Err(format!(
+                "it requires characters, which don't appear in {} addresses",
+                format_name(format)
+            ));
+        }
+        let invalid = invalid
+            .iter()
+            .map(|c| format!("`{}`", c.escape_ascii()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(match format {
+            AddressFormat::Ss58(_) => format!(
+                "{} can't appear in base58 address, which consists of 1-9, a-z (excl. l) and A-Z (excl. I, O)",
+                invalid
+            ),
+            AddressFormat::H160 => format!(
+                "{} can't appear in h160 address, which is 0x followed by hex digits",
+                invalid
+            ),
+        });
+    }
+    let lengths = format.address_len();
+    if !analysis.may_match(
+        |position| match (format, position) {
+            (AddressFormat::H160, 0..=1) => chars(position),
+            _ => &alphabet[..],
+        },
+        lengths.clone(),
+    ) {
+        return Err(format!(
+            "it needs address of different length, {} addresses have {} characters",
+            format_name(format),
+            if lengths.start() == lengths.end() {
+                lengths.start().to_string()
+            } else {
+                format!("{} to {}", lengths.start(), lengths.end())
+            }
+        ));
+    }
+    if !analysis.may_match(chars, lengths) {
+        return Err(format!(
+            "{} addresses can only start with {}",
+            format_name(format),
+            first
+                .iter()
+                .map(|c| format!("`{}`", *c as char))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn format_name(format: AddressFormat) -> String {
+    match (format, format.name()) {
+        (AddressFormat::H160, _) => "h160".to_string(),
+        (_, Some(name)) => format!("{} ({})", name, format),
+        (_, None) => format!("format {}", format),
+    }
+}
+
+/// ASCII characters of literals in pattern
+fn literal_chars(hir: &Hir, out: &mut Vec<u8>) {
+    match hir.kind() {
+        HirKind::Literal(literal) => out.extend(literal.0.iter().filter(|c| c.is_ascii())),
+        HirKind::Repetition(repetition) => literal_chars(&repetition.sub, out),
+        HirKind::Capture(capture) => literal_chars(&capture.sub, out),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            hirs.iter().for_each(|hir| literal_chars(hir, out))
+        }
+        HirKind::Empty | HirKind::Class(_) | HirKind::Look(_) => {}
+    }
+}
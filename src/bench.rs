@@ -0,0 +1,376 @@
+use crate::{
+    human_count, human_duration, Account, AddressFormat, Error, Estimate, Matcher, Pattern, Scheme,
+    Search, SearchBuilder, SeedSource,
+};
+use rand::thread_rng;
+use std::fmt::{self, Display};
+use std::fs;
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Candidates per measurement of search steps by [`Bench`]
+const BENCH_KEYS: u32 = 10_000;
+
+/// Average time per key, spent on each step of search
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CostSplit {
+    /// Generating random seed and its public key
+    pub keygen: Duration,
+    /// Encoding address
+    pub encoding: Duration,
+    /// Matching address against regex
+    pub matching: Duration,
+}
+
+impl CostSplit {
+    /// Run each step separately for `keys` candidates, in the current thread
+    ///
+    /// Candidates are generated and encoded the same way as [`Account::generate`] does.
+    /// Regex is matched against every address, while search skips some by matcher filters
+    pub fn measure(scheme: Scheme, matcher: &Matcher, keys: u32) -> Self {
+        let mut rng = thread_rng();
+        let started = Instant::now();
+        let mut accounts = (0..keys)
+            .map(|_| Account::candidate(&mut rng, scheme, &SeedSource::Raw, matcher.format()))
+            .collect::<Vec<_>>();
+        let keygen = started.elapsed();
+
+        let started = Instant::now();
+        accounts.iter_mut().for_each(Account::encode);
+        let encoding = started.elapsed();
+
+        let started = Instant::now();
+        let matched = accounts
+            .iter()
+            .filter(|account| account.is_match(matcher.regex()))
+            .count();
+        let matching = started.elapsed();
+        black_box(matched);
+
+        let keys = keys.max(1);
+        Self {
+            keygen: keygen / keys,
+            encoding: encoding / keys,
+            matching: matching / keys,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.keygen + self.encoding + self.matching
+    }
+}
+
+/// Keys per second, checked by search running for `duration`
+///
+/// Search runs the same worker threads as usual, so all of its overheads are accounted
+pub fn search_speed(search: SearchBuilder, duration: Duration) -> Result<f64, Error> {
+    let search = search.spawn()?;
+    thread::sleep(duration);
+    let speed = search.attempts() as f64 / search.elapsed().as_secs_f64();
+    search.stop();
+    Ok(speed)
+}
+
+/// Speed of search for every supported combination of key schemes and address formats
+pub struct Bench {
+    targets: Vec<(Scheme, AddressFormat, String, Matcher)>,
+    skipped: Vec<(Scheme, AddressFormat)>,
+    threads: u8,
+    duration: Duration,
+}
+
+impl Bench {
+    /// All schemes and polkadot format are measured by default
+    ///
+    /// Regex should be valid in every format. By default, it's a rare word anywhere in address,
+    /// so every address is encoded and matched
+    pub fn new(
+        schemes: &[Scheme],
+        formats: &[AddressFormat],
+        regex: Option<&str>,
+    ) -> Result<Self, Error> {
+        let schemes = match schemes.is_empty() {
+            true => Scheme::ALL,
+            false => schemes,
+        };
+        let formats = match formats.is_empty() {
+            true => &[AddressFormat::Ss58(0)],
+            false => formats,
+        };
+        let mut targets = Vec::new();
+        let mut skipped = Vec::new();
+        for &format in formats {
+            let regex = regex.map(str::to_string).unwrap_or_else(|| match format {
+                AddressFormat::Ss58(_) => "Fancy".to_string(),
+                AddressFormat::H160 => "c0ffee".to_string(),
+            });
+            let matcher = Matcher::new(&regex, format)?;
+            for &scheme in schemes {
+                if format.supports(scheme) {
+                    targets.push((scheme, format, regex.clone(), matcher.clone()));
+                } else {
+                    skipped.push((scheme, format));
+                }
+            }
+        }
+        Ok(Self {
+            targets,
+            skipped,
+            threads: available_threads(),
+            duration: Duration::from_secs(5),
+        })
+    }
+    /// Measure search with 1 up to this many threads, defaults to number of cpus
+    pub fn threads(mut self, threads: u8) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+    /// How long search runs for each thread count, defaults to 5 seconds
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Combinations, which aren't measured, as format isn't supported by scheme
+    pub fn skipped(&self) -> &[(Scheme, AddressFormat)] {
+        &self.skipped
+    }
+    /// Whether there is nothing to measure
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Measure cost of search steps and speed of search, returns JSON report
+    ///
+    /// With `tables`, results are also printed as tables, while they are measured
+    pub fn run(&self, tables: bool) -> Result<serde_json::Value, Error> {
+        let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+        let cpu = fs::read_to_string("/proc/cpuinfo").ok().and_then(|info| {
+            info.lines()
+                .find_map(|line| line.strip_prefix("model name"))
+                .map(|name| name.trim_start_matches([' ', '\t', ':']).to_string())
+        });
+        if tables {
+            if let Some(cpu) = &cpu {
+                println!("cpu: {}", cpu);
+            }
+            println!(
+                "{} cpus, measuring {} searches for {} each",
+                cpus,
+                self.targets.len() * self.threads as usize,
+                human_duration(self.duration.as_secs_f64())
+            );
+            println!();
+            println!("cost per key, single thread");
+            println!(
+                "{:<8} {:<16} {:>15} {:>15} {:>15}",
+                "scheme", "format", "keygen", "encoding", "regex"
+            );
+        }
+
+        let mut costs = Vec::new();
+        for (scheme, format, _, matcher) in &self.targets {
+            let cost = CostSplit::measure(*scheme, matcher, BENCH_KEYS);
+            if tables {
+                let share = |step: Duration| {
+                    format!(
+                        "{:.2}µs {:>3.0}%",
+                        step.as_secs_f64() * 1e6,
+                        step.as_secs_f64() / cost.total().as_secs_f64() * 100.0
+                    )
+                };
+                println!(
+                    "{:<8} {:<16} {:>15} {:>15} {:>15}",
+                    scheme.to_string(),
+                    format_label(*format),
+                    share(cost.keygen),
+                    share(cost.encoding),
+                    share(cost.matching)
+                );
+            }
+            costs.push(cost);
+        }
+
+        if tables {
+            println!();
+            println!("search speed, keys per second");
+            println!(
+                "{:<8} {:<16} {:>7} {:>12} {:>12} {:>8}",
+                "scheme", "format", "threads", "total", "per thread", "scaling"
+            );
+        }
+        let mut results = Vec::new();
+        for ((scheme, format, regex, _), cost) in self.targets.iter().zip(costs) {
+            let mut speeds = Vec::new();
+            for t in 1..=self.threads {
+                let search = SearchBuilder::new(regex)
+                    .scheme(*scheme)
+                    .format(*format)
+                    .threads(t);
+                let speed = search_speed(search, self.duration)?;
+                let single = speeds.first().copied().unwrap_or(speed);
+                let scaling = speed / (single * t as f64);
+                if tables {
+                    println!(
+                        "{:<8} {:<16} {:>7} {:>12.0} {:>12.0} {:>7.0}%",
+                        scheme.to_string(),
+                        format_label(*format),
+                        t,
+                        speed,
+                        speed / t as f64,
+                        scaling * 100.0
+                    );
+                }
+                speeds.push(speed);
+            }
+            results.push(serde_json::json!({
+                "scheme": scheme.to_string(),
+                "format": format.to_string(),
+                "regex": regex,
+                "cost_ns": {
+                    "keygen": cost.keygen.as_nanos() as u64,
+                    "encoding": cost.encoding.as_nanos() as u64,
+                    "regex": cost.matching.as_nanos() as u64,
+                },
+                "threads": speeds.iter().enumerate().map(|(i, speed)| serde_json::json!({
+                    "threads": i + 1,
+                    "keys_per_sec": speed,
+                    "scaling": speed / (speeds[0] * (i + 1) as f64),
+                })).collect::<Vec<_>>(),
+            }));
+        }
+        Ok(serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "cpu": cpu,
+            "cpus": cpus,
+            "duration_secs": self.duration.as_secs_f64(),
+            "results": results,
+        }))
+    }
+}
+
+fn available_threads() -> u8 {
+    let cpus = thread::available_parallelism().map_or(1, |n| n.get());
+    cpus.min(u8::MAX as usize) as u8
+}
+
+/// Network name and prefix, as short as possible for tables
+fn format_label(format: AddressFormat) -> String {
+    match format.name() {
+        Some(name) if format != AddressFormat::H160 => format!("{} ({})", name, format),
+        _ => format.to_string(),
+    }
+}
+
+/// Expected time of search, from estimated probability of match and measured speed
+pub struct Forecast {
+    /// Keys per second
+    speed: f64,
+    targets: Targets,
+}
+
+enum Targets {
+    /// Estimate of the whole search, and how many matches it should find
+    Search(Estimate, usize),
+    /// Named patterns with their quotas, which are filled in parallel
+    Patterns(Vec<(Pattern, Option<Estimate>)>),
+    /// Search can never find a match, with explanation
+    Impossible(String),
+}
+
+impl Forecast {
+    /// Run `search` for `duration` to measure its speed, then stop it
+    ///
+    /// Search of named `patterns` is forecast to fill quota of each of them, otherwise to find
+    /// `limit` matches. `None` if search is too complex to estimate, it's stopped right away
+    pub fn measure(
+        search: Search,
+        patterns: &[Pattern],
+        limit: usize,
+        duration: Duration,
+    ) -> Option<Self> {
+        let targets = if patterns.is_empty() {
+            Targets::Search(search.estimate()?, limit)
+        } else {
+            let estimates = search.estimates().iter().copied();
+            Targets::Patterns(patterns.iter().cloned().zip(estimates).collect())
+        };
+        thread::sleep(duration);
+        let speed = search.attempts() as f64 / search.elapsed().as_secs_f64();
+        search.stop();
+        Some(Self { speed, targets })
+    }
+
+    /// Search, which can't be spawned as no key matches, see [`Error::NeverMatches`]
+    pub fn impossible(reason: impl Display) -> Self {
+        Self {
+            speed: 0.0,
+            targets: Targets::Impossible(reason.to_string()),
+        }
+    }
+}
+
+impl Display for Forecast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = |n: u64| if n == 1 { "" } else { "es" };
+        match &self.targets {
+            Targets::Search(estimate, limit) => {
+                writeln!(
+                    f,
+                    "probability: {}, {} attempts per match",
+                    estimate,
+                    human_count(estimate.attempts())
+                )?;
+                writeln!(f, "speed: {} attempts per second", human_count(self.speed))?;
+                writeln!(
+                    f,
+                    "expected time: {} for {} match{}",
+                    human_duration(estimate.attempts() * *limit as f64 / self.speed),
+                    limit,
+                    plural(*limit as u64)
+                )?;
+                for confidence in [0.5, 0.9, 0.99] {
+                    writeln!(
+                        f,
+                        "{:.0}% chance of a match within {}",
+                        confidence * 100.0,
+                        human_duration(estimate.attempts_for(confidence) / self.speed)
+                    )?;
+                }
+                Ok(())
+            }
+            Targets::Patterns(patterns) => {
+                writeln!(f, "speed: {} attempts per second", human_count(self.speed))?;
+                let mut slowest: f64 = 0.0;
+                for (pattern, estimate) in patterns {
+                    match estimate {
+                        Some(estimate) => {
+                            let secs = estimate.attempts() * pattern.quota as f64 / self.speed;
+                            slowest = slowest.max(secs);
+                            writeln!(
+                                f,
+                                "{}: {}, expected time {} for {} match{}",
+                                pattern.name,
+                                estimate,
+                                human_duration(secs),
+                                pattern.quota,
+                                plural(pattern.quota)
+                            )?;
+                        }
+                        None => writeln!(f, "{}: too complex to estimate", pattern.name)?,
+                    }
+                }
+                writeln!(
+                    f,
+                    "expected time for all quotas: at least {}",
+                    human_duration(slowest)
+                )
+            }
+            Targets::Impossible(reason) => {
+                writeln!(f, "probability: 0, {}", reason)?;
+                writeln!(f, "expected time: impossible")
+            }
+        }
+    }
+}
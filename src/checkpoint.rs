@@ -0,0 +1,361 @@
+use crate::keystore::{SCRYPT_LOG_N, SCRYPT_P, SCRYPT_R};
+use crate::{Account, AddressFormat, Error, Pattern, Scheme, Search};
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use xsalsa20poly1305::{
+    aead::{Aead, KeyInit},
+    XSalsa20Poly1305,
+};
+
+/// Bumped on incompatible changes of checkpoint file
+const VERSION: u32 = 2;
+
+/// Progress of a long-running search, which can be saved and resumed later
+///
+/// Public parts of found accounts are stored as is. Their secrets, and search parameters,
+/// which may include base secret for derivation, are encrypted with [`CheckpointKey`]
+#[derive(Clone, Default)]
+pub struct Checkpoint {
+    /// Parameters of the search, i.e command line arguments
+    pub params: Vec<String>,
+    /// Attempts made so far
+    pub attempts: u64,
+    pub elapsed: Duration,
+    /// Derivation search continues from this path index, see [`crate::Search::derivation_index`]
+    pub derivation_index: u64,
+    pub matches: Vec<Account>,
+}
+
+/// Key for secrets in checkpoint, derived from passphrase by scrypt
+#[derive(Clone)]
+pub struct CheckpointKey {
+    kdf: Kdf,
+    cipher: XSalsa20Poly1305,
+}
+
+impl CheckpointKey {
+    /// Derive new key with random salt, using the same scrypt parameters as keystore
+    pub fn new(passphrase: &str) -> Self {
+        let mut salt = [0; 32];
+        thread_rng().fill_bytes(&mut salt);
+        let kdf = Kdf {
+            salt: hex::encode(salt),
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+        };
+        Self::derive(passphrase, kdf).expect("default scrypt parameters are valid")
+    }
+
+    fn derive(passphrase: &str, kdf: Kdf) -> Result<Self, Error> {
+        let salt = decode_hex(&kdf.salt)?;
+        let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, 32)
+            .map_err(|e| Error::InvalidCheckpoint(format!("scrypt parameters: {}", e)))?;
+        let mut key = [0; 32];
+        scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut key)
+            .expect("output length is valid");
+        Ok(Self {
+            kdf,
+            cipher: XSalsa20Poly1305::new(&key.into()),
+        })
+    }
+
+    fn seal(&self, plain: &[u8]) -> (String, String) {
+        let mut nonce = [0; 24];
+        thread_rng().fill_bytes(&mut nonce);
+        let sealed = self
+            .cipher
+            .encrypt(&nonce.into(), plain)
+            .expect("buffer is large enough");
+        (hex::encode(nonce), hex::encode(sealed))
+    }
+
+    fn open(&self, nonce: &str, sealed: &str) -> Result<Vec<u8>, Error> {
+        let nonce: [u8; 24] = decode_hex(nonce)?
+            .try_into()
+            .map_err(|_| Error::InvalidCheckpoint("nonce should be 24 bytes".to_string()))?;
+        self.cipher
+            .decrypt(&nonce.into(), decode_hex(sealed)?.as_slice())
+            .map_err(|_| Error::WrongPassphrase)
+    }
+}
+
+impl Checkpoint {
+    /// Reduce quotas of `patterns` by matches found so far
+    pub fn remaining(&self, patterns: &mut [Pattern]) {
+        for matched in &self.matches {
+            if let Some(pattern) = patterns
+                .iter_mut()
+                .find(|p| matched.pattern.as_ref() == Some(&p.name))
+            {
+                pattern.quota = pattern.quota.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Replace file at `path`, file is never left half-written
+    pub fn save(&self, path: &Path, key: &CheckpointKey) -> Result<(), Error> {
+        let params = serde_json::to_vec(&self.params).expect("params are serializable");
+        let (params_nonce, params) = key.seal(&params);
+        let file = File {
+            version: VERSION,
+            params_nonce,
+            params,
+            attempts: self.attempts,
+            elapsed_secs: self.elapsed.as_secs_f64(),
+            derivation_index: self.derivation_index,
+            kdf: key.kdf.clone(),
+            matches: self
+                .matches
+                .iter()
+                .map(|m| SavedMatch::new(m, key))
+                .collect(),
+        };
+        let json = serde_json::to_string_pretty(&file).expect("checkpoint is serializable");
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        fs::write(&temp, json)?;
+        fs::rename(&temp, path)?;
+        Ok(())
+    }
+
+    /// Read checkpoint and decrypt found secrets
+    ///
+    /// Returned key should be used to save checkpoint again
+    pub fn load(path: &Path, passphrase: &str) -> Result<(Self, CheckpointKey), Error> {
+        let json = fs::read_to_string(path)?;
+        let file: File =
+            serde_json::from_str(&json).map_err(|e| Error::InvalidCheckpoint(e.to_string()))?;
+        if file.version != VERSION {
+            return Err(Error::InvalidCheckpoint(format!(
+                "unsupported version {}, expected {}",
+                file.version, VERSION
+            )));
+        }
+        let key = CheckpointKey::derive(passphrase, file.kdf)?;
+        let params = serde_json::from_slice(&key.open(&file.params_nonce, &file.params)?)
+            .map_err(|e| Error::InvalidCheckpoint(e.to_string()))?;
+        let matches = file
+            .matches
+            .into_iter()
+            .map(|m| m.open(&key))
+            .collect::<Result<_, _>>()?;
+        let checkpoint = Self {
+            params,
+            attempts: file.attempts,
+            elapsed: Duration::try_from_secs_f64(file.elapsed_secs)
+                .map_err(|e| Error::InvalidCheckpoint(e.to_string()))?,
+            derivation_index: file.derivation_index,
+            matches,
+        };
+        Ok((checkpoint, key))
+    }
+}
+
+/// Checkpoint of a running search, which is saved on every match and once in an interval
+pub struct Progress {
+    path: PathBuf,
+    checkpoint: Checkpoint,
+    key: CheckpointKey,
+    interval: Duration,
+    saved: Instant,
+}
+
+impl Progress {
+    /// Start a new checkpoint, `params` should be enough to restart the search
+    pub fn new(path: impl Into<PathBuf>, passphrase: &str, params: Vec<String>) -> Self {
+        Self::with_checkpoint(
+            path.into(),
+            Checkpoint {
+                params,
+                ..Checkpoint::default()
+            },
+            CheckpointKey::new(passphrase),
+        )
+    }
+
+    /// Continue checkpoint, saved at `path`
+    pub fn resume(path: impl Into<PathBuf>, passphrase: &str) -> Result<Self, Error> {
+        let path = path.into();
+        let (checkpoint, key) = Checkpoint::load(&path, passphrase)?;
+        Ok(Self::with_checkpoint(path, checkpoint, key))
+    }
+
+    fn with_checkpoint(path: PathBuf, checkpoint: Checkpoint, key: CheckpointKey) -> Self {
+        Self {
+            path,
+            checkpoint,
+            key,
+            interval: Duration::from_secs(60),
+            saved: Instant::now(),
+        }
+    }
+
+    /// How often checkpoint is saved without new matches, defaults to a minute
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    pub fn checkpoint(&self) -> &Checkpoint {
+        &self.checkpoint
+    }
+    /// Whether interval has passed since checkpoint was last saved
+    pub fn is_due(&self) -> bool {
+        self.saved.elapsed() >= self.interval
+    }
+
+    /// Add new match, unless it's already known
+    ///
+    /// Resumed derivation search might check a few paths again, their matches are skipped.
+    pub fn add(&mut self, account: Account) -> bool {
+        let known = self
+            .checkpoint
+            .matches
+            .iter()
+            .any(|m| m.address == account.address);
+        if !known {
+            self.checkpoint.matches.push(account);
+        }
+        !known
+    }
+
+    /// Save counters of search, matches of paths before `derivation_index` should be added already
+    pub fn save(&mut self, search: &Search, derivation_index: u64) -> Result<(), Error> {
+        self.checkpoint.attempts = search.attempts();
+        self.checkpoint.elapsed = search.elapsed();
+        self.checkpoint.derivation_index = derivation_index;
+        self.saved = Instant::now();
+        self.checkpoint.save(&self.path, &self.key)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct File {
+    version: u32,
+    params_nonce: String,
+    /// Encrypted [`Checkpoint::params`]
+    params: String,
+    attempts: u64,
+    elapsed_secs: f64,
+    derivation_index: u64,
+    kdf: Kdf,
+    matches: Vec<SavedMatch>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Kdf {
+    salt: String,
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+/// Public part of found account
+#[derive(Serialize, Deserialize)]
+struct SavedMatch {
+    scheme: String,
+    format: String,
+    public: String,
+    address: String,
+    other_addresses: Vec<(String, String)>,
+    matched: Option<String>,
+    pattern: Option<String>,
+    regexes: Vec<(String, String)>,
+    word: Option<(String, usize)>,
+    nonce: String,
+    /// Encrypted [`Secrets`]
+    secrets: String,
+}
+
+/// Secret part of found account, hex encoded
+#[derive(Serialize, Deserialize)]
+struct Secrets {
+    seed: Option<String>,
+    phrase: Option<String>,
+    path: Option<String>,
+    offset: Option<String>,
+    secret_key: Option<String>,
+}
+
+impl SavedMatch {
+    fn new(account: &Account, key: &CheckpointKey) -> Self {
+        let secrets = Secrets {
+            seed: account.seed.map(hex::encode),
+            phrase: account.phrase.clone(),
+            path: account.path.clone(),
+            offset: account.offset.map(hex::encode),
+            secret_key: account.secret_key.map(hex::encode),
+        };
+        let secrets = serde_json::to_vec(&secrets).expect("secrets are serializable");
+        let (nonce, secrets) = key.seal(&secrets);
+        Self {
+            scheme: account.scheme.to_string(),
+            format: account.format.to_string(),
+            public: hex::encode(&account.public),
+            address: account.address.clone(),
+            other_addresses: account
+                .other_addresses
+                .iter()
+                .map(|(format, address)| (format.to_string(), address.clone()))
+                .collect(),
+            matched: account.matched.clone(),
+            pattern: account.pattern.clone(),
+            regexes: account
+                .regexes
+                .iter()
+                .map(|(format, regex)| (format.to_string(), regex.clone()))
+                .collect(),
+            word: account.word.clone(),
+            nonce,
+            secrets,
+        }
+    }
+
+    fn open(self, key: &CheckpointKey) -> Result<Account, Error> {
+        let secrets: Secrets = serde_json::from_slice(&key.open(&self.nonce, &self.secrets)?)
+            .map_err(|e| Error::InvalidCheckpoint(e.to_string()))?;
+        let scheme: Scheme = self.scheme.parse().map_err(Error::InvalidCheckpoint)?;
+        let format: AddressFormat = self.format.parse().map_err(Error::InvalidCheckpoint)?;
+        let mut account = Account::unencoded(scheme, format, decode_hex(&self.public)?);
+        account.seed = secrets.seed.as_deref().map(decode_array).transpose()?;
+        account.phrase = secrets.phrase;
+        account.path = secrets.path;
+        account.offset = secrets.offset.as_deref().map(decode_array).transpose()?;
+        account.secret_key = secrets
+            .secret_key
+            .as_deref()
+            .map(decode_array)
+            .transpose()?;
+        account.address = self.address;
+        for (format, address) in self.other_addresses {
+            let format = format.parse().map_err(Error::InvalidCheckpoint)?;
+            account.other_addresses.push((format, address));
+        }
+        account.matched = self.matched;
+        account.pattern = self.pattern;
+        for (format, regex) in self.regexes {
+            let format = format.parse().map_err(Error::InvalidCheckpoint)?;
+            account.regexes.push((format, regex));
+        }
+        account.word = self.word;
+        Ok(account)
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, Error> {
+    // Value is not included into error, as it might be secret
+    hex::decode(s).map_err(|e| Error::InvalidCheckpoint(e.to_string()))
+}
+
+fn decode_array<const N: usize>(s: &str) -> Result<[u8; N], Error> {
+    decode_hex(s)?
+        .try_into()
+        .map_err(|_| Error::InvalidCheckpoint(format!("expected {} bytes", N)))
+}
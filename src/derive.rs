@@ -0,0 +1,208 @@
+use crate::{Account, AddressFormat, Error, Scheme};
+use sp_core::{
+    crypto::{Derive, DeriveJunction, Ss58Codec},
+    ecdsa, ed25519, sr25519, Pair,
+};
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Root pair, parsed from secret uri
+enum Root {
+    Sr25519(sr25519::Pair, Option<[u8; 32]>),
+    Ed25519(ed25519::Pair, Option<[u8; 32]>),
+    Ecdsa(ecdsa::Pair, Option<[u8; 32]>),
+    /// Only soft junctions can be derived, no secret is known
+    Sr25519Public(sr25519::Public),
+}
+
+/// Enumerates derivation paths of a single secret
+///
+/// Only the base secret needs to be backed up, found accounts are
+/// reported together with the path which should be appended to it.
+pub struct Derivation {
+    scheme: Scheme,
+    root: Root,
+    template: String,
+    next_index: AtomicU64,
+    /// First indices of batches, claimed by workers and not yet fully checked
+    claimed: Mutex<BTreeSet<u64>>,
+}
+
+impl Derivation {
+    /// `suri` is anything `subkey` accepts, i.e `phrase//hard/soft///password`
+    ///
+    /// `template` is a derivation path, where `{}` is replaced by attempt index,
+    /// i.e `//fancy//{}`. Password of `suri` applies to the whole path, so found
+    /// account is imported as `phrase//hard/soft//fancy//7///password`
+    pub fn new(scheme: Scheme, suri: &str, template: &str) -> Result<Self, Error> {
+        Self::with_root(scheme, Root::parse(scheme, suri)?, template)
+    }
+
+    /// Search using only sr25519 public key (hex or ss58 address), without knowing secret
+    ///
+    /// Only soft junctions are allowed in `template`, found path should be applied
+    /// to the owner's secret, i.e `subkey inspect "phrase/found/path"`
+    pub fn from_public(public: &str, template: &str) -> Result<Self, Error> {
+        let root = parse_sr25519_public(public)
+            .ok_or_else(|| Error::InvalidPublicKey(public.to_owned()))?;
+        Self::with_root(Scheme::Sr25519, Root::Sr25519Public(root), template)
+    }
+
+    fn with_root(scheme: Scheme, root: Root, template: &str) -> Result<Self, Error> {
+        // Password can't follow junctions, `junctions` would parse it as a hard one
+        if !template.contains("{}") || !template.starts_with('/') || template.contains("///") {
+            return Err(Error::InvalidPathTemplate(template.to_owned()));
+        }
+        let derivation = Self {
+            scheme,
+            root,
+            template: template.to_owned(),
+            next_index: AtomicU64::new(0),
+            claimed: Mutex::new(BTreeSet::new()),
+        };
+        // ed25519 and ecdsa only support hard junctions, and public keys only support soft
+        if derivation.derive(0, AddressFormat::Ss58(42)).is_none() {
+            return Err(Error::InvalidPathTemplate(template.to_owned()));
+        }
+        Ok(derivation)
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// Path for specified attempt index
+    pub fn path(&self, index: u64) -> String {
+        self.template.replace("{}", &index.to_string())
+    }
+
+    /// Continue enumeration from `index`, as returned by [`Derivation::checked`] of a previous run
+    pub fn start_at(self, index: u64) -> Self {
+        self.next_index.store(index, Ordering::Relaxed);
+        self
+    }
+
+    /// All paths before this index are checked, and their matches are sent
+    ///
+    /// Paths after it might be checked too, so resumed search can find some matches again.
+    pub fn checked(&self) -> u64 {
+        let claimed = self.claimed();
+        claimed
+            .first()
+            .copied()
+            .unwrap_or_else(|| self.next_index.load(Ordering::Relaxed))
+    }
+
+    /// Reserve `count` next indices for a worker, returns the first one
+    ///
+    /// Should be [released](Derivation::release) once all of them are checked.
+    pub(crate) fn claim(&self, count: u64) -> u64 {
+        let mut claimed = self.claimed();
+        let start = self.next_index.fetch_add(count, Ordering::Relaxed);
+        claimed.insert(start);
+        start
+    }
+    pub(crate) fn release(&self, start: u64) {
+        let mut claimed = self.claimed();
+        claimed.remove(&start);
+    }
+    fn claimed(&self) -> MutexGuard<'_, BTreeSet<u64>> {
+        self.claimed
+            .lock()
+            .expect("claimed batches aren't poisoned")
+    }
+
+    /// Derive account for the next not yet checked path, address is not encoded
+    pub(crate) fn next(&self, format: AddressFormat) -> Account {
+        self.nth(self.next_index.fetch_add(1, Ordering::Relaxed), format)
+    }
+    /// Derive account for claimed index, address is not encoded
+    pub(crate) fn nth(&self, index: u64, format: AddressFormat) -> Account {
+        self.derive_unencoded(index, format)
+            .expect("template was checked to be derivable")
+    }
+
+    /// Derive account for specified attempt index
+    pub fn derive(&self, index: u64, format: AddressFormat) -> Option<Account> {
+        self.derive_unencoded(index, format).map(Account::encoded)
+    }
+    fn derive_unencoded(&self, index: u64, format: AddressFormat) -> Option<Account> {
+        let path = self.path(index);
+        let (public, seed) = match &self.root {
+            Root::Sr25519(pair, seed) => derive_pair(pair, *seed, &path)
+                .map(|(pair, seed)| (pair.public().0.to_vec(), seed))?,
+            Root::Ed25519(pair, seed) => derive_pair(pair, *seed, &path)
+                .map(|(pair, seed)| (pair.public().0.to_vec(), seed))?,
+            Root::Ecdsa(pair, seed) => derive_pair(pair, *seed, &path)
+                .map(|(pair, seed)| (pair.public().0.to_vec(), seed))?,
+            Root::Sr25519Public(public) => (public.derive(junctions(&path))?.0.to_vec(), None),
+        };
+        Some(Account {
+            seed,
+            path: Some(path),
+            ..Account::unencoded(self.scheme, format, public)
+        })
+    }
+}
+
+impl Root {
+    fn parse(scheme: Scheme, suri: &str) -> Result<Self, Error> {
+        match scheme {
+            Scheme::Sr25519 => sr25519::Pair::from_string_with_seed(suri, None)
+                .map(|(pair, seed)| Root::Sr25519(pair, seed)),
+            Scheme::Ed25519 => ed25519::Pair::from_string_with_seed(suri, None)
+                .map(|(pair, seed)| Root::Ed25519(pair, seed)),
+            Scheme::Ecdsa => ecdsa::Pair::from_string_with_seed(suri, None)
+                .map(|(pair, seed)| Root::Ecdsa(pair, seed)),
+        }
+        .map_err(Error::InvalidSecretUri)
+    }
+}
+
+/// Public key and seed of pair for secret uri, seed is missing after soft junctions
+pub(crate) fn from_suri(scheme: Scheme, suri: &str) -> Result<(Vec<u8>, Option<[u8; 32]>), Error> {
+    Ok(match Root::parse(scheme, suri)? {
+        Root::Sr25519(pair, seed) => (pair.public().0.to_vec(), seed),
+        Root::Ed25519(pair, seed) => (pair.public().0.to_vec(), seed),
+        Root::Ecdsa(pair, seed) => (pair.public().0.to_vec(), seed),
+        Root::Sr25519Public(_) => unreachable!("secret uri always has secret"),
+    })
+}
+
+/// Parse public key from ss58 address of any network, or from hex
+pub(crate) fn parse_sr25519_public(public: &str) -> Option<sr25519::Public> {
+    sr25519::Public::from_ss58check_with_version(public)
+        .map(|(public, _)| public)
+        .ok()
+        .or_else(|| {
+            let bytes = hex::decode(public.trim_start_matches("0x")).ok()?;
+            sr25519::Public::try_from(bytes.as_slice()).ok()
+        })
+}
+
+fn derive_pair<P: Pair<Seed = [u8; 32]>>(
+    pair: &P,
+    seed: Option<[u8; 32]>,
+    path: &str,
+) -> Option<(P, Option<[u8; 32]>)> {
+    pair.derive(junctions(path), seed).ok()
+}
+
+/// Parse derivation path, the same way `SecretUri` does
+pub(crate) fn junctions(path: &str) -> impl Iterator<Item = DeriveJunction> + '_ {
+    // "//a/b" is split to ["", "", "a", "b"], empty part marks next junction as hard
+    let mut hard = false;
+    path.split('/').skip(1).filter_map(move |part| {
+        if part.is_empty() {
+            hard = true;
+            return None;
+        }
+        let junction = DeriveJunction::from(part);
+        Some(if std::mem::take(&mut hard) {
+            junction.harden()
+        } else {
+            junction
+        })
+    })
+}
@@ -0,0 +1,248 @@
+use crate::matcher::ALPHABET;
+use crate::Scheme;
+use num_bigint::BigUint;
+use secp256k1::PublicKey;
+use sp_core::{
+    crypto::{Ss58AddressFormat, Ss58Codec},
+    hashing::keccak_256,
+};
+use std::fmt::{self, Display};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// How account is represented as string
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AddressFormat {
+    /// Substrate address with specified network prefix
+    Ss58(u16),
+    /// Ethereum-style 20 byte address, EIP-55 checksummed
+    ///
+    /// Only available for ecdsa keys
+    H160,
+}
+
+/// SS58 prefix only supports 14 bits
+const MAX_SS58_FORMAT: u16 = 16383;
+
+impl AddressFormat {
+    /// Networks from ss58 registry, sorted by name
+    pub fn known() -> impl Iterator<Item = (&'static str, AddressFormat)> {
+        Ss58AddressFormat::all_names()
+            .iter()
+            .zip(Ss58AddressFormat::all())
+            .map(|(name, format)| (*name, AddressFormat::Ss58(*format as u16)))
+            .filter(|(_, format)| !format.is_reserved())
+    }
+
+    /// Network name from ss58 registry
+    pub fn name(self) -> Option<&'static str> {
+        Self::known()
+            .find(|(_, format)| *format == self)
+            .map(|(name, _)| name)
+    }
+
+    /// Whether ss58 prefix is reserved or out of range, such addresses can't be used
+    pub fn is_reserved(self) -> bool {
+        match self {
+            AddressFormat::Ss58(format) => matches!(format, 46 | 47) || format > MAX_SS58_FORMAT,
+            AddressFormat::H160 => false,
+        }
+    }
+
+    /// Number of characters in addresses of this format
+    pub fn address_len(self) -> RangeInclusive<usize> {
+        let format = match self {
+            AddressFormat::Ss58(format) => format,
+            AddressFormat::H160 => return 42..=42,
+        };
+        let prefix = ss58_prefix(format);
+        let body_bits: usize = 8 * (32 + 2);
+        if prefix[0] == 0 {
+            // Leading '1', followed by account id; every zero byte of account id
+            // is also encoded as '1', which keeps length about the same
+            let lo = BigUint::from(1u8) << (body_bits - 8 * 3);
+            let hi = (BigUint::from(1u8) << body_bits) - 1u8;
+            return 1 + base58_len(&lo)..=1 + base58_len(&hi);
+        }
+        let lo = BigUint::from_bytes_be(&prefix) << body_bits;
+        let hi = ((BigUint::from_bytes_be(&prefix) + 1u8) << body_bits) - 1u8;
+        base58_len(&lo)..=base58_len(&hi)
+    }
+
+    /// Leading characters, which are the same or almost the same for all addresses of this format
+    pub(crate) fn fixed_chars(self) -> usize {
+        match self {
+            AddressFormat::Ss58(_) => 1,
+            AddressFormat::H160 => 2,
+        }
+    }
+
+    /// Characters, which any address of this format may start with
+    pub fn leading_chars(self) -> Vec<char> {
+        let format = match self {
+            AddressFormat::Ss58(format) => format,
+            AddressFormat::H160 => return vec!['0'],
+        };
+        let prefix = ss58_prefix(format);
+        if prefix[0] == 0 {
+            // Leading zero byte is always encoded as '1'
+            return vec!['1'];
+        }
+        // Account id and checksum
+        let body_bits: usize = 8 * (32 + 2);
+        let lo = BigUint::from_bytes_be(&prefix) << body_bits;
+        let hi = ((BigUint::from_bytes_be(&prefix) + 1u8) << body_bits) - 1u8;
+        let mut chars = Vec::new();
+        let mut scale = BigUint::from(1u8);
+        while scale <= hi {
+            let next = &scale * 58u8;
+            // Values, which are encoded with the same number of digits
+            let from = (&lo).max(&scale);
+            let to = (&hi).min(&(&next - 1u8)).clone();
+            if *from <= to {
+                let first: u8 = (from / &scale).try_into().expect("digit");
+                let last: u8 = (to / &scale).try_into().expect("digit");
+                for digit in first..=last {
+                    let c = ALPHABET[digit as usize] as char;
+                    if !chars.contains(&c) {
+                        chars.push(c);
+                    }
+                }
+            }
+            scale = next;
+        }
+        chars.sort_unstable_by_key(|c| ALPHABET.iter().position(|a| *a as char == *c));
+        chars
+    }
+
+    pub fn supports(self, scheme: Scheme) -> bool {
+        match self {
+            AddressFormat::Ss58(_) => true,
+            AddressFormat::H160 => scheme == Scheme::Ecdsa,
+        }
+    }
+
+    /// Bytes, which are encoded in address: substrate account id for ss58, or last 20 bytes
+    /// of keccak-256 hash of public key for h160
+    pub fn account_id(self, scheme: Scheme, public: &[u8]) -> Vec<u8> {
+        match self {
+            AddressFormat::Ss58(_) => <[u8; 32]>::from(scheme.account_id(public)).to_vec(),
+            AddressFormat::H160 => h160(public).to_vec(),
+        }
+    }
+
+    /// Encode account with specified public key
+    pub fn address(self, scheme: Scheme, public: &[u8]) -> String {
+        match self {
+            AddressFormat::Ss58(format) => scheme
+                .account_id(public)
+                .to_ss58check_with_version(Ss58AddressFormat::custom(format)),
+            AddressFormat::H160 => to_checksum_address(&h160(public)),
+        }
+    }
+}
+impl From<u16> for AddressFormat {
+    fn from(format: u16) -> Self {
+        AddressFormat::Ss58(format)
+    }
+}
+impl Display for AddressFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressFormat::Ss58(format) => write!(f, "{}", format),
+            AddressFormat::H160 => f.write_str("h160"),
+        }
+    }
+}
+impl FromStr for AddressFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_lowercase();
+        if ["h160", "eth", "ethereum"].contains(&name.as_str()) {
+            return Ok(AddressFormat::H160);
+        }
+        let unusable = |format: String| {
+            format!(
+                "ss58 prefix {} can't be used: 46 and 47 are reserved, and prefix should not exceed {}",
+                format, MAX_SS58_FORMAT
+            )
+        };
+        let format = if !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit()) {
+            // Numbers beyond u16 are out of range too, rather than unknown names
+            AddressFormat::Ss58(s.parse().map_err(|_| unusable(s.to_string()))?)
+        } else {
+            AddressFormat::known()
+                .find(|(known, _)| {
+                    let known = known.to_ascii_lowercase();
+                    // Registry names some networks i.e unique_mainnet
+                    known == name || known.strip_suffix("mainnet").map(|n| n.trim_end_matches(['_', '-'])) == Some(&name)
+                })
+                .map(|(_, format)| format)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown address format: {}, expected ss58 prefix, network name or h160, see formats command",
+                        s
+                    )
+                })?
+        };
+        match format {
+            AddressFormat::Ss58(prefix) if format.is_reserved() => {
+                Err(unusable(prefix.to_string()))
+            }
+            _ => Ok(format),
+        }
+    }
+}
+
+fn base58_len(value: &BigUint) -> usize {
+    let mut len = 1;
+    let mut scale = BigUint::from(58u8);
+    while &scale <= value {
+        scale *= 58u8;
+        len += 1;
+    }
+    len
+}
+
+/// Bytes, which are prepended to account id in ss58 encoding
+pub(crate) fn ss58_prefix(format: u16) -> Vec<u8> {
+    // SS58 prefix only supports 14 bits
+    let ident = format & 0b0011_1111_1111_1111;
+    match ident {
+        0..=63 => vec![ident as u8],
+        _ => {
+            let first = ((ident & 0b0000_0000_1111_1100) as u8) >> 2;
+            let second = ((ident >> 8) as u8) | ((ident & 0b0000_0000_0000_0011) as u8) << 6;
+            vec![first | 0b01000000, second]
+        }
+    }
+}
+
+/// keccak-256 of uncompressed secp256k1 public key, last 20 bytes
+fn h160(public: &[u8]) -> [u8; 20] {
+    let public = PublicKey::from_slice(public)
+        .expect("valid compressed secp256k1 public key")
+        .serialize_uncompressed();
+    let hash = keccak_256(&public[1..]);
+    let mut out = [0; 20];
+    out.copy_from_slice(&hash[12..]);
+    out
+}
+
+/// EIP-55 mixed-case encoding
+fn to_checksum_address(address: &[u8; 20]) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak_256(lower.as_bytes());
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0xf;
+        out.push(if nibble >= 8 {
+            c.to_ascii_uppercase()
+        } else {
+            c
+        });
+    }
+    out
+}
@@ -0,0 +1,94 @@
+use crate::{Account, AddressFormat, Scheme};
+use curve25519_dalek::{
+    constants::{RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE},
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+};
+use rand::RngCore;
+
+/// Fast sr25519 search engine
+///
+/// Instead of expanding fresh mini-secret and doing scalar multiplication for
+/// every attempt, starts from a random secret scalar, and adds base point to the
+/// public key for every next attempt.
+///
+/// Found accounts have no seed or mnemonic, only expanded secret key (scalar
+/// and nonce), which is accepted by `sr25519::Pair::from_seed_slice` and
+/// polkadot.js keystore json, but not by `subkey` secret uri.
+///
+/// Accounts of the same batch differ by a known offset, so knowing one secret
+/// reveals the others, at most one account per batch should be used.
+#[derive(Default)]
+pub(crate) struct Incremental {
+    points: Vec<RistrettoPoint>,
+}
+
+impl Incremental {
+    /// Generate `count` consecutive accounts, starting from random secret
+    ///
+    /// Addresses are not encoded
+    pub fn fill<R: RngCore>(
+        &mut self,
+        rng: &mut R,
+        accounts: &mut Vec<Account>,
+        count: usize,
+        format: AddressFormat,
+    ) {
+        let mut wide = [0; 64];
+        rng.fill_bytes(&mut wide);
+        let mut half_scalar = Scalar::from_bytes_mod_order_wide(&wide);
+        let mut half_point = &half_scalar * &RISTRETTO_BASEPOINT_TABLE;
+
+        self.points.clear();
+        for _ in 0..count {
+            self.points.push(half_point);
+            half_point += RISTRETTO_BASEPOINT_POINT;
+        }
+        // Compression needs field inversion, which is batched here, the price is
+        // that published points are doubled, and so are the secret scalars
+        let publics = RistrettoPoint::double_and_compress_batch(&self.points);
+
+        let two = Scalar::from(2u8);
+        for public in publics {
+            let mut secret_key = [0; 64];
+            secret_key[..32].copy_from_slice((two * half_scalar).as_bytes());
+            // Nonce doesn't affect public key, and shouldn't be shared between accounts
+            rng.fill_bytes(&mut secret_key[32..]);
+            half_scalar += Scalar::one();
+
+            accounts.push(Account {
+                secret_key: Some(secret_key),
+                ..Account::unencoded(Scheme::Sr25519, format, public.0.to_vec())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+    use sp_core::{sr25519, Pair};
+
+    #[test]
+    fn secret_keys_derive_published_keys() {
+        let mut accounts = Vec::new();
+        let mut incremental = Incremental::default();
+        let format = AddressFormat::Ss58(42);
+        incremental.fill(&mut thread_rng(), &mut accounts, 64, format);
+        incremental.fill(&mut thread_rng(), &mut accounts, 3, format);
+        assert_eq!(accounts.len(), 67);
+        for account in &accounts {
+            let secret_key = account.secret_key.expect("secret key is set");
+            let pair = sr25519::Pair::from_seed_slice(&secret_key).unwrap();
+            assert_eq!(pair.public().0.as_slice(), account.public.as_slice());
+            // Nonce is usable for signing
+            let signature = pair.sign(b"fancy");
+            assert!(sr25519::Pair::verify(&signature, b"fancy", &pair.public()));
+        }
+        let mut publics = accounts.iter().map(|a| &a.public).collect::<Vec<_>>();
+        publics.sort();
+        publics.dedup();
+        assert_eq!(publics.len(), accounts.len());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{Account, AddressFormat, Error, Language, Scheme};
+use std::fmt::{self, Display};
+
+/// Account re-derived from secret, with its keys and addresses in several formats
+pub struct Inspection {
+    account: Account,
+    addresses: Vec<(AddressFormat, String)>,
+}
+
+impl Inspection {
+    /// Secret is accepted in any form printed by search, see [`Account::from_secret`]
+    ///
+    /// Addresses of polkadot, kusama and generic substrate are shown by default, and h160 one
+    /// for ecdsa keys
+    pub fn new(
+        scheme: Scheme,
+        secret: &str,
+        formats: &[AddressFormat],
+        language: Language,
+    ) -> Result<Self, Error> {
+        let mut formats = formats.to_vec();
+        if formats.is_empty() {
+            formats = vec![
+                AddressFormat::Ss58(0),
+                AddressFormat::Ss58(2),
+                AddressFormat::Ss58(42),
+            ];
+            if scheme == Scheme::Ecdsa {
+                formats.push(AddressFormat::H160);
+            }
+        }
+        if let Some(&format) = formats.iter().find(|f| !f.supports(scheme)) {
+            return Err(Error::UnsupportedFormat(scheme, format));
+        }
+        let mut account = Account::from_secret(scheme, formats[0], secret, language)?;
+        let addresses = formats
+            .into_iter()
+            .map(|format| (format, account.address_in(format).to_string()))
+            .collect();
+        Ok(Self { account, addresses })
+    }
+
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+}
+
+impl Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let account = &self.account;
+        if let Some(phrase) = &account.phrase {
+            writeln!(f, "{:<26}{}", "Secret phrase:", phrase)?;
+        }
+        if let Some(seed) = &account.seed {
+            writeln!(f, "{:<26}0x{}", "Secret seed:", hex::encode(seed))?;
+        }
+        if let Some(secret_key) = &account.secret_key {
+            writeln!(f, "{:<26}0x{}", "Secret key:", hex::encode(secret_key))?;
+        }
+        if let Some(path) = &account.path {
+            writeln!(f, "{:<26}{}", "Derivation path:", path)?;
+        }
+        writeln!(f, "{:<26}{}", "Scheme:", account.scheme)?;
+        writeln!(
+            f,
+            "{:<26}0x{}",
+            "Public key (hex):",
+            hex::encode(&account.public)
+        )?;
+        writeln!(
+            f,
+            "{:<26}0x{}",
+            "Account ID:",
+            hex::encode(account.account_id())
+        )?;
+        for (format, address) in &self.addresses {
+            let label = match (format, format.name()) {
+                (AddressFormat::H160, _) => "H160 address:".to_string(),
+                (_, Some(name)) => format!("SS58 address ({}):", name),
+                (_, None) => format!("SS58 address ({}):", format),
+            };
+            writeln!(f, "{:<26}{}", label, address)?;
+        }
+        Ok(())
+    }
+}
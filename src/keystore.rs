@@ -0,0 +1,128 @@
+use crate::{Account, Error, Scheme};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::{thread_rng, RngCore};
+use schnorrkel::{ExpansionMode, MiniSecretKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use xsalsa20poly1305::{
+    aead::{Aead, KeyInit},
+    XSalsa20Poly1305,
+};
+
+/// scrypt parameters used by polkadot.js, which refuses other ones
+pub(crate) const SCRYPT_LOG_N: u8 = 15;
+pub(crate) const SCRYPT_R: u32 = 8;
+pub(crate) const SCRYPT_P: u32 = 1;
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+
+/// Encrypted account JSON, which can be imported to polkadot.js apps, browser extension
+/// and compatible wallets
+///
+/// Only sr25519 and ed25519 accounts with secrets are supported
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    pub encoded: String,
+    pub encoding: Encoding,
+    pub address: String,
+    pub meta: Meta,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Encoding {
+    pub content: (String, String),
+    #[serde(rename = "type")]
+    pub kind: (String, String),
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    pub name: String,
+    pub genesis_hash: String,
+    /// Unix time in milliseconds
+    pub when_created: u64,
+}
+
+impl Keystore {
+    /// Encrypt account secret with passphrase, `name` is shown by wallets
+    pub fn new(
+        account: &Account,
+        passphrase: &str,
+        name: impl Into<String>,
+        when_created: u64,
+    ) -> Result<Self, Error> {
+        let secret = pkcs8_secret(account)?;
+        let mut pkcs8 = Vec::with_capacity(117);
+        pkcs8.extend_from_slice(&PKCS8_HEADER);
+        pkcs8.extend_from_slice(&secret);
+        pkcs8.extend_from_slice(&PKCS8_DIVIDER);
+        pkcs8.extend_from_slice(&account.public);
+
+        let mut salt = [0; 32];
+        let mut nonce = [0; 24];
+        thread_rng().fill_bytes(&mut salt);
+        thread_rng().fill_bytes(&mut nonce);
+        let params = scrypt::Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P, 64)
+            .expect("polkadot.js scrypt parameters are valid");
+        let mut key = [0; 64];
+        scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut key)
+            .expect("output length is valid");
+        // Only the first half of derived key is used by polkadot.js
+        let cipher = XSalsa20Poly1305::new_from_slice(&key[..32]).expect("key is 32 bytes");
+        let sealed = cipher
+            .encrypt(&nonce.into(), pkcs8.as_slice())
+            .expect("buffer is large enough");
+
+        let mut encoded = salt.to_vec();
+        for param in [1u32 << SCRYPT_LOG_N, SCRYPT_P, SCRYPT_R] {
+            encoded.extend_from_slice(&param.to_le_bytes());
+        }
+        encoded.extend_from_slice(&nonce);
+        encoded.extend_from_slice(&sealed);
+        Ok(Self {
+            encoded: STANDARD.encode(encoded),
+            encoding: Encoding {
+                content: ("pkcs8".to_string(), account.scheme.to_string()),
+                kind: ("scrypt".to_string(), "xsalsa20-poly1305".to_string()),
+                version: "3".to_string(),
+            },
+            address: account.address.clone(),
+            meta: Meta {
+                name: name.into(),
+                genesis_hash: String::new(),
+                when_created,
+            },
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("keystore is serializable")
+    }
+}
+
+/// 64 byte secret key in the form used by polkadot.js
+fn pkcs8_secret(account: &Account) -> Result<[u8; 64], Error> {
+    match (account.scheme, &account.seed, &account.secret_key) {
+        (Scheme::Sr25519, Some(seed), _) => Ok(MiniSecretKey::from_bytes(seed)
+            .expect("seed is 32 bytes")
+            .expand(ExpansionMode::Ed25519)
+            .to_ed25519_bytes()),
+        (Scheme::Sr25519, None, Some(secret_key)) => Ok(SecretKey::from_bytes(secret_key)
+            .map_err(|_| Error::NotExportable("secret key is malformed"))?
+            .to_ed25519_bytes()),
+        // Seed, followed by public key, as in tweetnacl
+        (Scheme::Ed25519, Some(seed), _) => {
+            let mut secret = [0; 64];
+            secret[..32].copy_from_slice(seed);
+            secret[32..].copy_from_slice(&account.public);
+            Ok(secret)
+        }
+        (Scheme::Ecdsa, _, _) => Err(Error::NotExportable(
+            "only sr25519 and ed25519 keys can be exported",
+        )),
+        _ => Err(Error::NotExportable(
+            "account has no secret, i.e it was found by split-key or soft derivation search",
+        )),
+    }
+}
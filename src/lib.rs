@@ -0,0 +1,169 @@
+//! Vanity SS58 address generator
+//!
+//! ```no_run
+//! use iwannafancyaddress::SearchBuilder;
+//!
+//! let search = SearchBuilder::new("^5Fancy").format(42).threads(4).spawn().unwrap();
+//! for account in search.iter().take(2) {
+//!     println!("{}", account);
+//! }
+//! ```
+
+use sp_core::crypto::SecretStringError;
+use std::fmt::{self, Display};
+use std::io;
+
+mod account;
+mod analysis;
+mod bench;
+mod checkpoint;
+mod derive;
+mod format;
+mod incremental;
+mod inspect;
+mod keystore;
+mod lookalike;
+mod matcher;
+mod record;
+mod report;
+mod score;
+mod search;
+mod split;
+mod verify;
+mod wordlist;
+
+pub use account::{Account, Scheme, SeedSource};
+pub use analysis::{estimate, Estimate};
+pub use bench::{search_speed, Bench, CostSplit, Forecast};
+pub use bip39::{Language, MnemonicType};
+pub use checkpoint::{Checkpoint, CheckpointKey, Progress};
+pub use derive::Derivation;
+pub use format::AddressFormat;
+use incremental::Incremental;
+pub use inspect::Inspection;
+pub use keystore::Keystore;
+pub use lookalike::Substitutions;
+pub use matcher::Matcher;
+pub use record::Record;
+pub use report::{human_count, human_duration, Export, OutputFormat, Reporter};
+use score::Leaderboard;
+pub use score::{Metrics, Scorer, Weights};
+pub use search::{Pattern, Require, Search, SearchBuilder};
+pub use split::{combine, SplitKey};
+pub use verify::{Verdict, Verifier};
+pub use wordlist::Wordlist;
+
+/// Error returned when search can't be started, or its results can't be saved or restored
+#[derive(Debug)]
+pub enum Error {
+    /// Pattern is not a valid regex
+    Regex(regex::Error),
+    /// Address format can't be used with this key scheme
+    UnsupportedFormat(Scheme, AddressFormat),
+    /// SS58 prefix is reserved or out of range
+    ReservedFormat(AddressFormat),
+    /// Named patterns can't be used as requested
+    InvalidPatterns(String),
+    /// Character of a word has no look-alike in base58 alphabet
+    NoSubstitute(char),
+    /// No word of wordlist can appear in addresses of the format
+    EmptyWordlist(AddressFormat),
+    /// Pattern can't match any address of the format, with explanation
+    NeverMatches(AddressFormat, String),
+    /// Base secret for derivation can't be parsed
+    InvalidSecretUri(SecretStringError),
+    /// Public key for derivation can't be parsed
+    InvalidPublicKey(String),
+    /// Feature is only supported for sr25519 keys
+    Sr25519Only(Scheme, &'static str),
+    /// Split-key offset is not a canonical scalar
+    InvalidOffset,
+    /// Derivation path template is malformed, has password, or uses junctions not supported by scheme
+    InvalidPathTemplate(String),
+    /// Checkpoint or keystore file can't be read or written
+    Io(io::Error),
+    /// Checkpoint file is malformed
+    InvalidCheckpoint(String),
+    /// Secrets in checkpoint can't be decrypted with this passphrase
+    WrongPassphrase,
+    /// Account can't be exported as keystore, with explanation
+    NotExportable(&'static str),
+    /// Secret to inspect is malformed, with explanation
+    InvalidSecret(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Regex(e) => write!(f, "invalid regex: {}", e),
+            Error::UnsupportedFormat(scheme, format) => {
+                write!(f, "{} address format is not supported by {} keys", format, scheme)
+            }
+            Error::ReservedFormat(format) => {
+                write!(f, "ss58 prefix {} is reserved or out of range", format)
+            }
+            Error::InvalidPatterns(reason) => write!(f, "invalid patterns: {}", reason),
+            Error::NoSubstitute(c) => {
+                write!(
+                    f,
+                    "`{}` is not in base58 alphabet, and has no substitutes, add some with --substitute '{}=…'",
+                    c, c
+                )?;
+                // Letter in the other case is the closest look-alike
+                let other = if c.is_ascii_uppercase() {
+                    c.to_ascii_lowercase()
+                } else {
+                    c.to_ascii_uppercase()
+                };
+                if other.is_ascii() && matcher::ALPHABET.contains(&(other as u8)) {
+                    write!(f, ", i.e '{}={}'", c, other)?;
+                }
+                Ok(())
+            }
+            Error::EmptyWordlist(format) => write!(
+                f,
+                "wordlist has no words, which can appear in {} addresses",
+                analysis::format_name(*format)
+            ),
+            Error::NeverMatches(format, reason) => write!(
+                f,
+                "pattern can never match {} addresses: {}",
+                analysis::format_name(*format),
+                reason
+            ),
+            Error::InvalidSecretUri(e) => write!(f, "invalid secret uri: {:?}", e),
+            Error::InvalidPublicKey(public) => write!(
+                f,
+                "invalid public key: {}, expected ss58 address or 32 byte hex",
+                public
+            ),
+            Error::Sr25519Only(scheme, feature) => {
+                write!(f, "{} is only supported for sr25519 keys, not {}", feature, scheme)
+            }
+            Error::InvalidOffset => write!(f, "split-key offset is not a valid scalar"),
+            Error::InvalidPathTemplate(template) => write!(
+                f,
+                "invalid derivation path template: {}, it should contain {{}} placeholder and no password (///), ed25519/ecdsa only support hard (//) junctions, public keys only support soft (/) junctions",
+                template
+            ),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::InvalidCheckpoint(reason) => write!(f, "invalid checkpoint: {}", reason),
+            Error::WrongPassphrase => write!(f, "wrong passphrase, secrets can't be decrypted"),
+            Error::NotExportable(reason) => write!(f, "can't export keystore: {}", reason),
+            Error::InvalidSecret(reason) => write!(f, "invalid secret: {}", reason),
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+impl From<regex::Error> for Error {
+    fn from(e: regex::Error) -> Self {
+        Error::Regex(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{matcher::ALPHABET, Error};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Look-alike characters, which may be shown instead of a letter of the word
+///
+/// Base58 has no `0`, `O`, `I` and `l`, so some letters can only be shown by substitutes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Substitutions {
+    table: BTreeMap<char, Vec<char>>,
+}
+
+impl Default for Substitutions {
+    /// Common leet substitutions
+    fn default() -> Self {
+        let table = [
+            ('a', "4"),
+            ('b', "8"),
+            ('e', "3"),
+            ('g', "9"),
+            ('i', "1"),
+            ('l', "1"),
+            ('s', "5"),
+            ('t', "7"),
+            ('z', "2"),
+        ];
+        Self {
+            table: table
+                .into_iter()
+                .map(|(c, with)| (c, with.chars().collect()))
+                .collect(),
+        }
+    }
+}
+
+impl Substitutions {
+    /// Table without any substitutions
+    pub fn empty() -> Self {
+        Self {
+            table: BTreeMap::new(),
+        }
+    }
+    /// Add or replace substitutes of a (case-insensitive) character
+    pub fn set(&mut self, c: char, with: impl IntoIterator<Item = char>) {
+        self.table
+            .insert(c.to_ascii_lowercase(), with.into_iter().collect());
+    }
+    /// Replace entries with the ones from `other`
+    pub fn extend(&mut self, other: Substitutions) {
+        self.table.extend(other.table);
+    }
+
+    /// Regex, which matches `word` with any letter replaced by its substitutes
+    ///
+    /// Leading `^` and trailing `$` are kept as anchors, everything else is treated literally.
+    pub fn pattern(&self, word: &str, ignore_case: bool) -> Result<String, Error> {
+        let (start, word) = match word.strip_prefix('^') {
+            Some(word) => ("^", word),
+            None => ("", word),
+        };
+        let (word, end) = match word.strip_suffix('$') {
+            Some(word) => (word, "$"),
+            None => (word, ""),
+        };
+        let mut pattern = start.to_string();
+        for c in word.chars() {
+            let mut variants = vec![c];
+            if ignore_case {
+                variants.push(c.to_ascii_lowercase());
+                variants.push(c.to_ascii_uppercase());
+            }
+            if let Some(with) = self.table.get(&c.to_ascii_lowercase()) {
+                variants.extend(with);
+            }
+            variants.retain(|v| v.is_ascii() && ALPHABET.contains(&(*v as u8)));
+            variants.sort_unstable();
+            variants.dedup();
+            match variants.as_slice() {
+                [] => return Err(Error::NoSubstitute(c)),
+                [single] => pattern.push(*single),
+                _ => {
+                    pattern.push('[');
+                    pattern.extend(variants);
+                    pattern.push(']');
+                }
+            }
+        }
+        pattern.push_str(end);
+        Ok(pattern)
+    }
+}
+
+impl Display for Substitutions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self
+            .table
+            .iter()
+            .map(|(c, with)| format!("{}={}", c, with.iter().collect::<String>()))
+            .collect::<Vec<_>>();
+        f.write_str(&entries.join(","))
+    }
+}
+
+impl FromStr for Substitutions {
+    type Err = String;
+
+    /// Comma separated entries, i.e `a=4,o=9q,e=`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut table = Self::empty();
+        for entry in s.split(',').filter(|e| !e.is_empty()) {
+            let (c, with) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid substitution: {}, expected i.e a=4", entry))?;
+            let mut chars = c.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => table.set(c, with.chars()),
+                _ => return Err(format!("substituted should be a single character: {}", c)),
+            }
+        }
+        Ok(table)
+    }
+}
@@ -1,24 +1,129 @@
-use clap::Parser;
-use rand::{thread_rng, RngCore};
-use regex::Regex;
+use clap::{Parser, Subcommand};
+use iwannafancyaddress::{
+    combine, AddressFormat, Bench, Checkpoint, Error, Export, Forecast, Inspection, Language,
+    MnemonicType, OutputFormat, Pattern, Progress, Record, Reporter, Require, Scheme, Scorer,
+    Search, SearchBuilder, SeedSource, Substitutions, Verifier, Weights,
+};
 use sp_core::{
-    crypto::{AccountId32, Ss58AddressFormat, Ss58Codec},
+    crypto::{AccountId32, Ss58Codec},
     sr25519, Pair,
 };
-use std::fmt::{self, Display};
-use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
-use std::{
-    thread,
-    time::{Duration, SystemTime},
-};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// How long search runs to measure speed for estimate
+const MEASURE_TIME: Duration = Duration::from_secs(5);
 
 #[derive(Parser)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Opts {
-    /// Network address format
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(flatten)]
+    search: SearchOpts,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Add offset, found by split-key search, to your secret
+    Combine(CombineOpts),
+    /// List known networks, their ss58 prefixes and characters their addresses start with
+    Formats,
+    /// Estimate, how long search with the same options would take
+    ///
+    /// Runs search for a few seconds to measure speed
+    Estimate(Box<SearchOpts>),
+    /// Re-derive account from found secret, and print its public key and addresses
+    ///
+    /// Uses the same derivation as search, so output is consistent with its results
+    Inspect(InspectOpts),
+    /// Re-derive results of search from their secrets, and check they still match patterns
+    ///
+    /// Exits with non-zero code, if any result is inconsistent or can't be parsed
+    Verify(VerifyOpts),
+    /// Measure speed of search for each key scheme, address format and thread count
+    ///
+    /// Reports cost of every search step, and how speed scales with threads
+    Bench(BenchOpts),
+}
+
+#[derive(Parser)]
+struct SearchOpts {
+    /// Network address format: ss58 prefix or network name
+    ///
+    /// I.e 0 or polkadot, 7391 or unique, see formats command for the full list.
+    /// Use h160 for ethereum-style addresses (ecdsa scheme only).
+    /// May be repeated to check the same key in several networks
+    #[clap(
+        long,
+        short = 'f',
+        required_unless_present = "resume",
+        multiple_occurrences = true,
+        use_value_delimiter = true
+    )]
+    format: Vec<AddressFormat>,
+    /// With several formats, report key if any of them matches, instead of all
+    #[clap(long)]
+    any: bool,
+    /// Match letters in any case, i.e fancy also matches FaNcY
+    #[clap(long, short = 'i')]
+    ignore_case: bool,
+    /// Treat regexes as plain words, letters of which may be replaced by look-alikes
+    ///
+    /// Default table is a=4, b=8, e=3, g=9, i=1, l=1, s=5, t=7, z=2.
+    /// Leading ^ and trailing $ are still treated as anchors
+    #[clap(long)]
+    lookalike: bool,
+    /// Add or replace look-alike substitutions, i.e "o=9q,e=", implies --lookalike
+    #[clap(long)]
+    substitute: Option<Substitutions>,
+    /// Key scheme: sr25519, ed25519 or ecdsa
+    ///
+    /// Resulting seed can only be imported using the same scheme
+    #[clap(long, short = 's', default_value = "sr25519")]
+    scheme: Scheme,
+    /// Instead of random seeds, search over derivation paths of this secret uri
+    ///
+    /// Accepts the same values as subkey, i.e "phrase", "phrase///password", "0xseed//base".
+    /// Password goes after the found path, when importing: "phrase//found//path///password"
+    #[clap(long, conflicts_with = "mnemonic")]
+    derive_from: Option<String>,
+    /// Search over soft derivation paths of this sr25519 public key or address
+    ///
+    /// No secret is needed, so search may be run on untrusted machine.
+    /// Use with soft path template, i.e --path "/fancy/{}", then apply found path to your secret
+    #[clap(long, conflicts_with_all = &["mnemonic", "derive-from"])]
+    derive_from_public: Option<String>,
+    /// Search for split-key offset to this sr25519 public key or address
     ///
-    /// Unique is 7391
-    #[clap(long, short = 'f')]
-    format: u16,
+    /// Found offset is useless without the secret, use combine command to get the final key
+    #[clap(long, conflicts_with_all = &["mnemonic", "derive-from", "derive-from-public"])]
+    split_key: Option<String>,
+    /// Derivation path template, {} is replaced by attempt index
+    #[clap(long, default_value = "//{}")]
+    path: String,
+    /// Use fast incremental search engine (sr25519 only)
+    ///
+    /// Instead of generating new key for every attempt, adds base point to the previous one.
+    /// Much faster, but yields expanded secret keys instead of seeds, which can't be restored from
+    /// mnemonic, and are not accepted by subkey
+    #[clap(long, conflicts_with_all = &["mnemonic", "derive-from", "derive-from-public", "split-key"])]
+    incremental: bool,
+    /// Generate BIP39 phrases with specified word count (12, 15, 18, 21 or 24) instead of raw seeds
+    #[clap(long, short = 'm', parse(try_from_str = parse_mnemonic_type))]
+    mnemonic: Option<MnemonicType>,
+    /// Wordlist language for generated phrases
+    ///
+    /// en, zh-hans, zh-hant, fr, it, ja, ko, es
+    #[clap(long, default_value = "en", parse(try_from_str = parse_language))]
+    language: Language,
     /// How many threads to use
     #[clap(long, short = 't', default_value = "1")]
     threads: u8,
@@ -28,128 +133,670 @@ struct Opts {
     /// Regex, which generated address should match
     ///
     /// Make sure your requests are within valid ss58 alphabet:
-    /// 1-9, a-z (excl. l), A-Z (excl. I, O)
-    regex: String,
+    /// 1-9, a-z (excl. l), A-Z (excl. I, O).
+    /// With several formats, either one regex for all of them, or one regex per format, in the same order
+    #[clap(required_unless_present_any = &["patterns", "score", "wordlist", "resume"])]
+    regex: Vec<String>,
+    /// Search for several named patterns at once, instead of regex
+    ///
+    /// File has one pattern per line: name, regex and optional quota (defaults to 1), separated by
+    /// whitespace. Empty lines and lines starting with # are skipped. Limit is the sum of quotas
+    #[clap(long, conflicts_with = "regex")]
+    patterns: Option<PathBuf>,
+    /// Address should contain any word from this file, one word per line
+    ///
+    /// Words are searched anywhere after leading characters, all at once, which is much faster than
+    /// alternation regex. Words with characters outside address alphabet are skipped,
+    /// use --ignore-case to match letters in any case. If regex is given, address should match it too
+    #[clap(long, conflicts_with_all = &["patterns", "lookalike", "substitute"])]
+    wordlist: Option<PathBuf>,
+    /// Skip wordlist words shorter than this
+    #[clap(long, default_value = "1", requires = "wordlist")]
+    min_length: usize,
+    /// Instead of stopping at the first matches, keep the most attractive ones
+    ///
+    /// Addresses are scored by longest repeated run, ascending/descending sequence, palindrome,
+    /// dictionary word coverage and pronounceable stretch. If regex is given, only matching
+    /// addresses are scored. Press enter to print the leaderboard, it is also printed once
+    /// --duration runs out
+    #[clap(long, conflicts_with = "patterns")]
+    score: bool,
+    /// How many addresses to keep on the leaderboard
+    #[clap(long, default_value = "10", requires = "score")]
+    top: usize,
+    /// Override metric weights, i.e "run=2,words=0"
+    ///
+    /// Defaults are run=1, sequence=1, palindrome=0.5, words=1, pronounceable=0.5
+    #[clap(long, requires = "score")]
+    weights: Option<Weights>,
+    /// File with one dictionary word per line, replaces built-in dictionary
+    #[clap(long, requires = "score")]
+    dictionary: Option<PathBuf>,
+    /// Stop scoring after this time, i.e 90s, 30m, 2h or 1d
+    #[clap(long, requires = "score", parse(try_from_str = parse_duration))]
+    duration: Option<Duration>,
+    /// Save progress and found matches to this file, to continue search later with --resume
+    ///
+    /// Secrets of found matches are encrypted with passphrase
+    #[clap(long, conflicts_with = "score")]
+    checkpoint: Option<PathBuf>,
+    /// How often checkpoint is saved, it is also saved on every match
+    #[clap(long, default_value = "1m", parse(try_from_str = parse_duration))]
+    checkpoint_interval: Duration,
+    /// Continue search from checkpoint, with the options it was started with
+    ///
+    /// Attempts, elapsed time and found matches are restored, so limit and quotas of patterns
+    /// continue where they left off. Relative paths are resolved from the current directory
+    #[clap(
+        long,
+        conflicts_with_all = &["format", "regex", "patterns", "wordlist", "score", "checkpoint"]
+    )]
+    resume: Option<PathBuf>,
+    /// Save every match to this directory as polkadot.js account JSON, encrypted with passphrase
+    ///
+    /// Files can be imported to polkadot.js apps, browser extension and compatible wallets.
+    /// Secrets of exported matches are not printed. Only sr25519 and ed25519 keys are supported
+    #[clap(long, conflicts_with_all = &["score", "split-key", "derive-from-public"])]
+    keystore: Option<PathBuf>,
+    /// Read passphrase for checkpoint and keystore from the first line of this file, instead of
+    /// prompting
+    #[clap(long)]
+    passphrase_file: Option<PathBuf>,
+    /// How matches are printed: text, json, ndjson or csv
+    ///
+    /// Structured formats have one record per match, with address, keys, secrets, matched
+    /// pattern, attempt count and unix timestamp. Progress, estimates and warnings are then
+    /// printed to stderr as JSON lines with "event" field. Prefer ndjson over json for searches,
+    /// which may be interrupted, as json array is only closed once search is done
+    #[clap(long, default_value = "text", conflicts_with = "score")]
+    output_format: OutputFormat,
 }
 
-#[derive(Default, Clone)]
-struct Account {
-    seed: [u8; 32],
+#[derive(Parser)]
+struct CombineOpts {
+    /// Address, found by split-key search
     address: String,
+    /// Offset, found by split-key search
+    #[clap(long)]
+    offset: String,
+    /// Your secret uri, public key of which was passed to --split-key
+    #[clap(long)]
+    secret: String,
 }
 
-impl Account {
-    fn generate<R: RngCore>(rng: &mut R, addr_format: u16) -> Account {
-        let mut seed = <sr25519::Pair as Pair>::Seed::default();
-        rng.fill_bytes(seed.as_mut());
-        let pair = sr25519::Pair::from_seed(&seed);
+#[derive(Parser)]
+struct InspectOpts {
+    /// Seed or secret key hex, phrase or secret uri, i.e `phrase//hard/soft///password`
+    ///
+    /// Read from the first line of stdin if missing, to keep secret out of shell history
+    secret: Option<String>,
+    /// Key scheme: sr25519, ed25519 or ecdsa
+    #[clap(long, short = 's', default_value = "sr25519")]
+    scheme: Scheme,
+    /// Network address formats to print, polkadot, kusama and generic substrate by default
+    #[clap(
+        long,
+        short = 'f',
+        multiple_occurrences = true,
+        use_value_delimiter = true
+    )]
+    format: Vec<AddressFormat>,
+    /// Wordlist language of phrase
+    #[clap(long, default_value = "en", parse(try_from_str = parse_language))]
+    language: Language,
+}
 
-        let address = AccountId32::from(pair.public())
-            .to_ss58check_with_version(Ss58AddressFormat::custom(addr_format));
-        Self { seed, address }
-    }
+#[derive(Parser)]
+struct VerifyOpts {
+    /// Files with output of search, in any output format
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+    /// Regex, which addresses should match, any of them if repeated
+    ///
+    /// Regexes, recorded by search, are checked anyway
+    #[clap(long, short = 'r', multiple_occurrences = true)]
+    regex: Vec<String>,
+    /// Named patterns, which search was started with, results should match their pattern
+    #[clap(long)]
+    patterns: Option<PathBuf>,
+    /// Match letters of regexes and patterns in any case
+    #[clap(long, short = 'i')]
+    ignore_case: bool,
+    /// Wordlist language of phrases
+    #[clap(long, default_value = "en", parse(try_from_str = parse_language))]
+    language: Language,
 }
-impl Display for Account {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}, seed = 0x{}", self.address, hex::encode(&self.seed))
-    }
+
+#[derive(Parser)]
+struct BenchOpts {
+    /// Key schemes to measure, all of them by default
+    #[clap(
+        long,
+        short = 's',
+        multiple_occurrences = true,
+        use_value_delimiter = true
+    )]
+    scheme: Vec<Scheme>,
+    /// Network address formats to measure, polkadot by default
+    #[clap(
+        long,
+        short = 'f',
+        multiple_occurrences = true,
+        use_value_delimiter = true
+    )]
+    format: Vec<AddressFormat>,
+    /// Measure search with 1 up to this many threads, number of CPUs by default
+    #[clap(long, short = 't')]
+    threads: Option<u8>,
+    /// How long search runs for each thread count
+    #[clap(long, default_value = "5s", parse(try_from_str = parse_duration))]
+    duration: Duration,
+    /// Regex to search for, should be valid in every format
+    ///
+    /// By default, a rare word anywhere in address, so every address is encoded and matched
+    #[clap(long, short = 'r')]
+    regex: Option<String>,
+    /// Print JSON report instead of tables
+    #[clap(long)]
+    json: bool,
 }
 
-/// How many accounts should be generated before matching all of them
-const THREAD_BATCH_SIZE: usize = 5000;
+fn parse_mnemonic_type(s: &str) -> Result<MnemonicType, String> {
+    let words = s.parse().map_err(|_| format!("not a number: {}", s))?;
+    MnemonicType::for_word_count(words).map_err(|e| e.to_string())
+}
 
-fn worker_thread(
-    tx: Sender<Account>,
-    attempts_tx: Sender<u64>,
-    kill_pill: Receiver<()>,
-    regex: Regex,
-    addr_type: u16,
-) {
-    let mut attempts: u64 = 0;
-    let mut thread_rng = thread_rng();
-    let mut accounts = Vec::with_capacity(THREAD_BATCH_SIZE);
+fn parse_language(s: &str) -> Result<Language, String> {
+    Language::from_language_code(s).ok_or_else(|| format!("unknown wordlist language: {}", s))
+}
 
-    loop {
-        for _ in 0..THREAD_BATCH_SIZE {
-            accounts.push(Account::generate(&mut thread_rng, addr_type));
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(split) => s.split_at(split),
+        None => (s, "s"),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration: {}", s))?;
+    let unit = match unit {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86400.0,
+        _ => {
+            return Err(format!(
+                "unknown duration unit: {}, expected s, m, h or d",
+                unit
+            ))
         }
-        attempts += THREAD_BATCH_SIZE as u64;
-        for account in accounts.drain(..) {
-            if regex.is_match(&account.address) {
-                tx.send(account.clone()).unwrap();
-            }
-        }
-        attempts_tx.send(attempts).unwrap();
-        attempts = 0;
-        match kill_pill.try_recv() {
-            Ok(_) | Err(TryRecvError::Disconnected) => {
-                break;
-            }
-            Err(TryRecvError::Empty) => {}
+    };
+    Ok(Duration::from_secs_f64(number * unit))
+}
+
+/// Passphrase from file, or from terminal, `confirm` asks to enter it twice
+fn read_passphrase(file: Option<&Path>, confirm: bool) -> String {
+    if let Some(path) = file {
+        let passphrase = fs::read_to_string(path)
+            .unwrap_or_else(|e| fail(format!("can't read {}: {}", path.display(), e)));
+        return passphrase.lines().next().unwrap_or_default().to_string();
+    }
+    let passphrase = rpassword::prompt_password("passphrase: ")
+        .unwrap_or_else(|e| fail(format!("can't read passphrase: {}", e)));
+    if confirm {
+        let repeated = rpassword::prompt_password("repeat passphrase: ")
+            .unwrap_or_else(|e| fail(format!("can't read passphrase: {}", e)));
+        if repeated != passphrase {
+            fail("passphrases don't match");
         }
     }
+    passphrase
+}
+
+fn fail(e: impl Display) -> ! {
+    eprintln!("{}", e);
+    process::exit(1);
 }
 
 fn main() {
     let opts = Opts::parse();
+    match opts.command {
+        Some(Command::Combine(opts)) => run_combine(opts),
+        Some(Command::Formats) => run_formats(),
+        Some(Command::Estimate(opts)) => run_estimate(*opts),
+        Some(Command::Inspect(opts)) => run_inspect(opts),
+        Some(Command::Verify(opts)) => run_verify(opts),
+        Some(Command::Bench(opts)) => run_bench(opts),
+        None => run_search(opts.search),
+    }
+}
 
-    let regex = Regex::new(&opts.regex).unwrap();
+fn run_combine(opts: CombineOpts) {
+    let (_, format) = AccountId32::from_ss58check_with_version(&opts.address)
+        .unwrap_or_else(|e| fail(format!("invalid address: {:?}", e)));
+    let offset: [u8; 32] = hex::decode(opts.offset.trim_start_matches("0x"))
+        .ok()
+        .and_then(|offset| offset.try_into().ok())
+        .unwrap_or_else(|| fail("offset should be 32 byte hex"));
 
-    let (tx, rx) = mpsc::channel();
-    let (attempt_count_tx, attempt_count_rx) = mpsc::channel();
-    let mut children = Vec::new();
-    let mut kill_pills = Vec::new();
-    for _ in 0..opts.threads {
-        let thread_tx = tx.clone();
-        let thread_attempt_count_tx = attempt_count_tx.clone();
-        let thread_matcher = regex.clone();
-        let (kill_pill_tx, kill_pill_rx) = mpsc::channel();
-        let child = thread::spawn(move || {
-            worker_thread(
-                thread_tx,
-                thread_attempt_count_tx,
-                kill_pill_rx,
-                thread_matcher,
-                opts.format,
-            )
-        });
-        kill_pills.push(kill_pill_tx);
-        children.push(child);
-    }
-
-    let start_time = SystemTime::now();
-    let mut matches_found: usize = 0;
-    let mut total_attempts: u64 = 0;
-    while matches_found < opts.limit {
-        match rx.recv_timeout(Duration::from_secs(3)) {
-            Ok(matched) => {
-                matches_found += 1;
-                println!("{}. {}", matches_found, matched);
+    let account = combine(&opts.secret, &offset, AddressFormat::Ss58(format.prefix()))
+        .unwrap_or_else(|e| fail(e));
+    if account.address != opts.address {
+        let public = sr25519::Pair::from_string(&opts.secret, None).map(|pair| pair.public());
+        fail(format!(
+            "combined key has address {}, which doesn't match {}, was search started with public key {}?",
+            account.address,
+            opts.address,
+            public.map(|p| p.to_ss58check_with_version(format)).unwrap_or_default(),
+        ));
+    }
+    println!("{}", account);
+}
+
+fn run_inspect(opts: InspectOpts) {
+    let secret = opts.secret.unwrap_or_else(|| {
+        let mut line = String::new();
+        io::stdin()
+            .read_line(&mut line)
+            .unwrap_or_else(|e| fail(format!("can't read secret: {}", e)));
+        line
+    });
+    let inspection = Inspection::new(opts.scheme, &secret, &opts.format, opts.language)
+        .unwrap_or_else(|e| fail(e));
+    print!("{}", inspection);
+}
+
+fn run_verify(opts: VerifyOpts) {
+    let patterns = opts.patterns.as_deref().map(read_patterns);
+    let verifier = Verifier::new(opts.language)
+        .regexes(&opts.regex, opts.ignore_case)
+        .and_then(|v| v.patterns(patterns.as_deref().unwrap_or_default(), opts.ignore_case))
+        .unwrap_or_else(|e| fail(e));
+    let (mut valid, mut invalid, mut unverified, mut unchecked) = (0, 0, 0, 0);
+    for path in &opts.files {
+        let results = fs::read_to_string(path)
+            .unwrap_or_else(|e| fail(format!("can't read {}: {}", path.display(), e)));
+        for (line, record) in Record::parse_results(&results) {
+            let location = format!("{}:{}", path.display(), line);
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    println!("{}: can't parse: {}", location, e);
+                    invalid += 1;
+                    continue;
+                }
+            };
+            let verdict = verifier.check(&record);
+            for problem in &verdict.problems {
+                println!("{}: {}: {}", location, record.address, problem);
+            }
+            if !verdict.problems.is_empty() {
+                invalid += 1;
+                continue;
+            }
+            if let Some(reason) = verdict.unverified {
+                println!(
+                    "{}: {}: secret not checked, {}",
+                    location, record.address, reason
+                );
+                unverified += 1;
+            }
+            if verdict.pattern_unchecked {
+                println!(
+                    "{}: {}: pattern not checked, pass --regex or --patterns",
+                    location, record.address
+                );
+                unchecked += 1;
             }
-            Err(RecvTimeoutError::Disconnected) => panic!("wallet tx disconnected"),
-            Err(RecvTimeoutError::Timeout) => {}
-        }
-        total_attempts += attempt_count_rx.try_iter().sum::<u64>();
-
-        if let Ok(elapsed) = start_time.elapsed() {
-            let elapsed_secs = elapsed.as_secs();
-            if elapsed_secs != 0 {
-                eprintln!(
-                    "{} attempts per second, {:.6} matches per second. {:.10}% of total matched: {}/{}",
-                    total_attempts / elapsed.as_secs(),
-                    matches_found as f64 / elapsed.as_secs() as f64,
-                    matches_found as f64 / total_attempts as f64,
-                    matches_found,
-                    opts.limit,
-                )
+            if verdict.unverified.is_none() && !verdict.pattern_unchecked {
+                valid += 1;
             }
         }
     }
+    eprintln!(
+        "{} results are valid, {} are inconsistent, {} have no secret to check, {} have no pattern to check",
+        valid, invalid, unverified, unchecked
+    );
+    if invalid > 0 {
+        process::exit(1);
+    }
+    if valid + unverified + unchecked == 0 {
+        fail("no results found");
+    }
+}
 
-    for pill in kill_pills {
-        pill.send(()).unwrap();
+fn run_bench(opts: BenchOpts) {
+    let mut bench = Bench::new(&opts.scheme, &opts.format, opts.regex.as_deref())
+        .unwrap_or_else(|e| fail(e))
+        .duration(opts.duration);
+    if let Some(threads) = opts.threads {
+        bench = bench.threads(threads);
     }
-    for child in children {
-        child.join().unwrap();
+    if !opts.json {
+        for (scheme, format) in bench.skipped() {
+            eprintln!(
+                "skipped: {} address format is not supported by {} keys",
+                format, scheme
+            );
+        }
+    }
+    if bench.is_empty() {
+        fail("no supported combination of scheme and format");
+    }
+    let report = bench.run(!opts.json).unwrap_or_else(|e| fail(e));
+    if opts.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("report is serializable")
+        );
+    }
+}
+
+fn run_formats() {
+    let mut known = AddressFormat::known().collect::<Vec<_>>();
+    known.sort_by_key(|(_, format)| match format {
+        AddressFormat::Ss58(prefix) => *prefix,
+        AddressFormat::H160 => u16::MAX,
+    });
+    println!("{:>6}  {:<24} starts with", "prefix", "network");
+    for (name, format) in known {
+        let chars = format.leading_chars().into_iter().collect::<String>();
+        println!("{:>6}  {:<24} {}", format.to_string(), name, chars);
+    }
+}
+
+/// One word per line, empty lines and lines starting with # are skipped
+fn read_words(path: &Path) -> Vec<String> {
+    let words = fs::read_to_string(path)
+        .unwrap_or_else(|e| fail(format!("can't read {}: {}", path.display(), e)));
+    words
+        .lines()
+        .map(str::trim)
+        .filter(|w| !w.is_empty() && !w.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn read_patterns(path: &Path) -> Vec<Pattern> {
+    let patterns = fs::read_to_string(path)
+        .unwrap_or_else(|e| fail(format!("can't read {}: {}", path.display(), e)));
+    let mut out = Vec::new();
+    for (i, line) in patterns.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let pattern = line
+            .parse()
+            .unwrap_or_else(|e| fail(format!("{}:{}: {}", path.display(), i + 1, e)));
+        out.push(pattern);
+    }
+    if out.is_empty() {
+        fail(format!("{} has no patterns", path.display()));
+    }
+    out
+}
+
+/// `patterns` are read from patterns file, if any, counters continue from `resumed` checkpoint
+fn spawn_search(
+    opts: SearchOpts,
+    patterns: &[Pattern],
+    resumed: Option<&Checkpoint>,
+    reporter: &Reporter,
+) -> Result<Search, Error> {
+    let source = match opts.mnemonic {
+        Some(words) => SeedSource::Mnemonic(words, opts.language),
+        None if opts.incremental => SeedSource::Incremental,
+        None => SeedSource::Raw,
+    };
+
+    // Print patterns with variants, unless only wordlist or scoring is used
+    let variants = (opts.ignore_case || opts.lookalike || opts.substitute.is_some())
+        && (!opts.regex.is_empty() || !patterns.is_empty());
+    let regexes = match (opts.regex.len(), opts.format.len()) {
+        // Named patterns are checked in the first format, scoring and wordlist accept any address
+        (0, formats) => vec![String::new(); formats],
+        (1, formats) => vec![opts.regex[0].clone(); formats],
+        (regexes, formats) if regexes == formats => opts.regex,
+        (regexes, formats) => fail(format!(
+            "got {} regexes for {} formats, expected either one regex, or one per format",
+            regexes, formats
+        )),
+    };
+    let mut targets = opts.format.into_iter().zip(regexes);
+    let (format, regex) = targets.next().expect("format is required");
+    let builder = if patterns.is_empty() {
+        SearchBuilder::new(regex)
+    } else {
+        SearchBuilder::with_patterns(patterns.to_vec())
+    };
+    let mut builder = builder
+        .scheme(opts.scheme)
+        .seed_source(source)
+        .format(format)
+        .require(if opts.any { Require::Any } else { Require::All })
+        .ignore_case(opts.ignore_case)
+        .threads(opts.threads);
+    if let Some(checkpoint) = resumed {
+        builder = builder.resume(checkpoint);
+    }
+    if opts.lookalike || opts.substitute.is_some() {
+        let mut substitutions = Substitutions::default();
+        if let Some(substitute) = opts.substitute {
+            substitutions.extend(substitute);
+        }
+        builder = builder.lookalike(substitutions);
+    }
+    for (format, regex) in targets {
+        builder = builder.other_format(format, regex);
+    }
+    if let Some(path) = opts.wordlist {
+        let mut words = read_words(&path);
+        words.retain(|w| w.chars().count() >= opts.min_length);
+        builder = builder.wordlist(words);
+    }
+    if opts.score {
+        let mut scorer = Scorer::new(opts.weights.unwrap_or_default());
+        if let Some(path) = opts.dictionary {
+            scorer = scorer.dictionary(read_words(&path));
+        }
+        builder = builder.score(scorer, opts.top);
+    }
+    if let Some(suri) = opts.derive_from {
+        builder = builder.derive_from(suri, opts.path);
+    } else if let Some(public) = opts.derive_from_public {
+        builder = builder.derive_from_public(public, opts.path);
+    } else if let Some(public) = opts.split_key {
+        builder = builder.split_key(public);
+    }
+    let search = builder.spawn()?;
+    if let Some(wordlist) = search.wordlist() {
+        let skipped = wordlist.skipped();
+        if !skipped.is_empty() {
+            reporter.notice(
+                "warning",
+                format!(
+                    "skipped {} words, which can't appear in addresses: {}{}",
+                    skipped.len(),
+                    skipped[..skipped.len().min(5)].join(", "),
+                    if skipped.len() > 5 { ", ..." } else { "" }
+                ),
+            );
+        }
+    }
+    for matcher in search.matchers() {
+        if variants {
+            reporter.notice(
+                "pattern",
+                format!("{}: {}", matcher.format(), matcher.regex()),
+            );
+        }
+        for warning in matcher.warnings() {
+            reporter.notice("warning", format!("{}: {}", matcher.format(), warning));
+        }
+    }
+    Ok(search)
+}
+
+/// Matches, which should be found, patterns file overrides limit
+fn limit(limit: usize, patterns: &[Pattern]) -> usize {
+    if patterns.is_empty() {
+        limit
+    } else {
+        patterns.iter().map(|p| p.quota as usize).sum()
+    }
+}
+
+fn run_estimate(opts: SearchOpts) {
+    if opts.score {
+        fail("scoring search runs until stopped, there is nothing to estimate");
+    }
+    if opts.resume.is_some() {
+        fail("estimate accepts search options, not checkpoint");
+    }
+    let limit = opts.limit;
+    let patterns = opts
+        .patterns
+        .as_deref()
+        .map(read_patterns)
+        .unwrap_or_default();
+    // Workers stop once quotas are filled, which would spoil measured speed
+    let unlimited = patterns
+        .iter()
+        .map(|p| Pattern {
+            quota: u64::MAX,
+            ..p.clone()
+        })
+        .collect::<Vec<_>>();
+    let search = match spawn_search(opts, &unlimited, None, &Reporter::new(OutputFormat::Text)) {
+        Ok(search) => search,
+        // Nothing to measure, no key matches whatever the speed is
+        Err(e @ Error::NeverMatches(..)) => {
+            print!("{}", Forecast::impossible(e));
+            return;
+        }
+        Err(e) => fail(e),
+    };
+    let forecast = Forecast::measure(search, &patterns, limit, MEASURE_TIME)
+        .unwrap_or_else(|| fail("pattern is too complex to estimate"));
+    print!("{}", forecast);
+}
+
+fn run_search(mut opts: SearchOpts) {
+    if opts.score {
+        run_score(opts);
+        return;
+    }
+    let (progress, passphrase) = open_checkpoint(&mut opts);
+    let mut progress = progress.map(|p| p.interval(opts.checkpoint_interval));
+    let mut patterns = opts
+        .patterns
+        .as_deref()
+        .map(read_patterns)
+        .unwrap_or_default();
+    let limit = limit(opts.limit, &patterns);
+    let mut reporter = Reporter::new(opts.output_format);
+    if let (Some(dir), Some(passphrase)) = (opts.keystore.clone(), passphrase) {
+        if opts.scheme == Scheme::Ecdsa {
+            fail("only sr25519 and ed25519 keys can be exported to keystore");
+        }
+        fs::create_dir_all(&dir)
+            .unwrap_or_else(|e| fail(format!("can't create {}: {}", dir.display(), e)));
+        reporter = reporter.export(Export::new(dir, passphrase));
+    }
+
+    if let Some(progress) = &progress {
+        reporter.resume(progress);
+        progress.checkpoint().remaining(&mut patterns);
+    }
+    let resumed = progress.as_ref().map(Progress::checkpoint);
+    let search = spawn_search(opts, &patterns, resumed, &reporter).unwrap_or_else(|e| fail(e));
+    reporter.run(&search, limit, progress.as_mut());
+    search.stop();
+}
+
+/// Checkpoint to resume or to start, and passphrase for it and keystore
+///
+/// Resumed search continues with options it was started with, they replace `opts`
+fn open_checkpoint(opts: &mut SearchOpts) -> (Option<Progress>, Option<String>) {
+    if let Some(path) = opts.resume.take() {
+        let passphrase = read_passphrase(opts.passphrase_file.as_deref(), false);
+        let progress = Progress::resume(&path, &passphrase)
+            .unwrap_or_else(|e| fail(format!("can't resume {}: {}", path.display(), e)));
+        *opts = SearchOpts::try_parse_from(&progress.checkpoint().params)
+            .unwrap_or_else(|e| fail(format!("invalid options in {}: {}", path.display(), e)));
+        opts.checkpoint = Some(path);
+        return (Some(progress), Some(passphrase));
+    }
+    if opts.checkpoint.is_none() && opts.keystore.is_none() {
+        return (None, None);
+    }
+    let passphrase = read_passphrase(opts.passphrase_file.as_deref(), true);
+    let progress = opts
+        .checkpoint
+        .clone()
+        .map(|path| Progress::new(path, &passphrase, env::args().collect()));
+    (progress, Some(passphrase))
+}
+
+/// Score matches until duration runs out, printing leaderboard on demand
+fn run_score(opts: SearchOpts) {
+    let duration = opts.duration;
+    let search = spawn_search(opts, &[], None, &Reporter::new(OutputFormat::Text))
+        .unwrap_or_else(|e| fail(e));
+    let mut requests = Some(leaderboard_requests());
+    eprintln!("press enter to print the leaderboard");
+    loop {
+        let mut timeout = Duration::from_secs(3);
+        if let Some(duration) = duration {
+            timeout = timeout.min(duration.saturating_sub(search.elapsed()));
+        }
+        match requests.as_ref().map(|r| r.recv_timeout(timeout)) {
+            Some(Ok(())) => print_leaderboard(&search),
+            Some(Err(RecvTimeoutError::Timeout)) => {}
+            // Stdin is closed, leaderboard is only printed at the end
+            Some(Err(RecvTimeoutError::Disconnected)) => requests = None,
+            None => thread::sleep(timeout),
+        }
+        if duration.is_some_and(|duration| search.elapsed() >= duration) {
+            break;
+        }
+        let elapsed_secs = search.elapsed().as_secs();
+        if let Some(attempts_per_second) = search.attempts().checked_div(elapsed_secs) {
+            let best = search.leaderboard().first().map(|(score, _)| *score);
+            eprintln!(
+                "{} attempts per second, best score {:.1}",
+                attempts_per_second,
+                best.unwrap_or(0.0)
+            );
+        }
+    }
+    search.cancel();
+    print_leaderboard(&search);
+    search.stop();
+}
+
+/// Every line entered to stdin is a request to print the leaderboard
+fn leaderboard_requests() -> Receiver<()> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            if line.is_err() || tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn print_leaderboard(search: &Search) {
+    let scorer = search.scorer().expect("search is in scoring mode");
+    for (rank, (score, account)) in search.leaderboard().iter().enumerate() {
+        println!(
+            "{}. score {:.1} ({}): {}",
+            rank + 1,
+            score,
+            scorer.metrics(&account.address),
+            account
+        );
     }
 }
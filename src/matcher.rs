@@ -0,0 +1,648 @@
+use crate::{
+    analysis::{check_reachable, format_name, measure},
+    format::ss58_prefix,
+    Account, AddressFormat, Error, Scheme,
+};
+use num_bigint::BigUint;
+use regex::Regex;
+use regex_syntax::hir::{
+    literal::{ExtractKind, Extractor},
+    Look,
+};
+use sp_core::hashing::blake2_512;
+
+/// Base58 alphabet, used by ss58
+pub(crate) const ALPHABET: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Length of ss58 account id + checksum
+const ACCOUNT_ID_LEN: usize = 32;
+const CHECKSUM_LEN: usize = 2;
+/// Checksum has 2^16 values, which is more than 58^2 but less than 58^3,
+/// so last 3 characters of address are affected by it
+pub(crate) const CHECKSUM_TAIL_CHARS: usize = 3;
+/// Longer suffixes are truncated, to make their value fit u128 arithmetic
+const MAX_SUFFIX_CHARS: usize = 10;
+
+/// Inclusive lower and exclusive upper bounds of account ids, `None` is 2^256
+pub(crate) type IdRange = ([u8; ACCOUNT_ID_LEN], Option<[u8; ACCOUNT_ID_LEN]>);
+
+/// Compiled address pattern
+#[derive(Clone)]
+pub struct Matcher {
+    regex: Regex,
+    format: AddressFormat,
+    prefix: Option<PrefixFilter>,
+    suffix: Option<SuffixFilter>,
+    warnings: Vec<String>,
+    /// Whether matched text is saved to [`Account::matched`]
+    report: bool,
+}
+
+impl Matcher {
+    pub fn new(pattern: &str, format: AddressFormat) -> Result<Self, Error> {
+        let regex = Regex::new(pattern)?;
+        check_reachable(pattern, format).map_err(|reason| Error::NeverMatches(format, reason))?;
+        let mut warnings = Vec::new();
+        let (prefix, suffix) = match format {
+            AddressFormat::Ss58(format) => {
+                let suffix = SuffixFilter::new(pattern, format);
+                if let Some(warning) = checksum_warning(pattern, suffix.as_ref()) {
+                    warnings.push(warning);
+                }
+                let prefix = PrefixFilter::new(pattern, format);
+                if prefix.as_ref().is_some_and(PrefixFilter::never_matches) {
+                    return Err(Error::NeverMatches(
+                        AddressFormat::Ss58(format),
+                        "no address starts with required prefix, its characters after the first are limited by network prefix too".to_string(),
+                    ));
+                }
+                (prefix, suffix)
+            }
+            // Matched in two cases, no point in filtering
+            AddressFormat::H160 => (None, None),
+        };
+        Ok(Self {
+            regex,
+            format,
+            prefix,
+            suffix,
+            warnings,
+            report: false,
+        })
+    }
+
+    /// Save matched part of address, to show which variant of pattern was hit
+    pub(crate) fn report_matched(mut self) -> Self {
+        self.report = true;
+        self
+    }
+
+    /// Problems with the pattern, which don't prevent search from running
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+    /// Network format, in which addresses are checked
+    pub fn format(&self) -> AddressFormat {
+        self.format
+    }
+
+    /// Whether cheap prefix filter is used for this pattern
+    pub fn has_prefix_filter(&self) -> bool {
+        self.prefix.is_some()
+    }
+    /// Whether suffix is checked before encoding address
+    pub fn has_suffix_filter(&self) -> bool {
+        self.suffix.is_some()
+    }
+    /// Account ids, which may produce addresses with required prefix, `None` if any id may
+    pub(crate) fn id_ranges(&self) -> Option<Vec<IdRange>> {
+        self.prefix.as_ref().map(PrefixFilter::allowed)
+    }
+
+    /// Refuse patterns, which can't match keys of `scheme`
+    pub(crate) fn check_scheme(&self, scheme: Scheme) -> Result<(), Error> {
+        if scheme == Scheme::Sr25519
+            && self
+                .prefix
+                .as_ref()
+                .is_some_and(PrefixFilter::never_matches_sr25519)
+        {
+            return Err(Error::NeverMatches(
+                self.format,
+                "no sr25519 address starts with required prefix, as sr25519 public keys always have even first byte, try ed25519 or ecdsa scheme".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check candidate in matcher's format, address is only encoded if filters don't reject it
+    pub fn is_match(&self, account: &mut Account) -> bool {
+        if self.prefix.is_some() || self.suffix.is_some() {
+            let id = account.account_id();
+            if let Some(prefix) = &self.prefix {
+                if !prefix.may_match(id.as_ref()) {
+                    return false;
+                }
+            }
+            if let Some(suffix) = &self.suffix {
+                if !suffix.may_match(id.as_ref()) {
+                    return false;
+                }
+            }
+        }
+        if !account.is_match_in(self.format, &self.regex) {
+            return false;
+        }
+        if self.report && account.matched.is_none() {
+            let address = account.address_in(self.format);
+            let matched = self
+                .regex
+                .find(address)
+                .map(|m| m.as_str().to_string())
+                .or_else(|| {
+                    // h160 might have matched in lowercase
+                    let address = address.to_lowercase();
+                    self.regex.find(&address).map(|m| m.as_str().to_string())
+                });
+            // Pattern may be empty, i.e when only wordlist is used
+            account.matched = matched.filter(|m| !m.is_empty());
+        }
+        true
+    }
+}
+
+/// Ranges of account ids, which may produce address with required prefix
+///
+/// SS58 address is base58 of big-endian integer (format ‖ account id ‖ checksum),
+/// so fixed leading characters correspond to a contiguous range of this integer.
+/// Checksum only affects the lowest bytes, range is widened to include any checksum.
+#[derive(Clone)]
+struct PrefixFilter {
+    /// For zero format byte (polkadot), account id starting with zero byte adds
+    /// extra leading '1', such ids are not filtered, if any prefix allows it
+    zero_head: bool,
+    ranges: Vec<IdRange>,
+}
+
+impl PrefixFilter {
+    /// `None` if pattern is not anchored, or has no literal prefix
+    fn new(pattern: &str, format: u16) -> Option<Self> {
+        let hir = regex_syntax::parse(pattern).ok()?;
+        if !hir.properties().look_set_prefix().contains(Look::Start) {
+            return None;
+        }
+        let seq = Extractor::new().kind(ExtractKind::Prefix).extract(&hir);
+        let literals = seq.literals()?;
+
+        let prefix = ss58_prefix(format);
+        // Each leading zero byte is encoded as '1'
+        let zeros = prefix.iter().take_while(|b| **b == 0).count();
+        let head = &prefix[zeros..];
+
+        let mut ranges = Vec::new();
+        let mut zero_head = false;
+        for literal in literals {
+            let literal = literal.as_bytes();
+            let (ones, digits) = literal.split_at(zeros.min(literal.len()));
+            if ones.iter().any(|c| *c != b'1') {
+                // Can't ever match
+                continue;
+            }
+            if digits.is_empty() {
+                // Any address matches
+                return None;
+            }
+            // Zero leading byte of account id adds another '1'
+            zero_head |= head.is_empty() && digits[0] == b'1';
+            ranges.extend(id_ranges(head, digits));
+        }
+        Some(Self { zero_head, ranges })
+    }
+
+    fn never_matches(&self) -> bool {
+        !self.zero_head && self.ranges.is_empty()
+    }
+    /// Whether every range only has ids with the same odd first byte
+    fn never_matches_sr25519(&self) -> bool {
+        !self.zero_head
+            && self.ranges.iter().all(|(lo, hi)| {
+                let mut next = [0; ACCOUNT_ID_LEN];
+                next[0] = lo[0].wrapping_add(1);
+                lo[0] % 2 == 1 && lo[0] != 0xff && hi.is_some_and(|hi| hi <= next)
+            })
+    }
+
+    /// Ranges of ids, which pass the filter, sorted and not overlapping
+    fn allowed(&self) -> Vec<IdRange> {
+        let mut ranges = self.ranges.clone();
+        if self.zero_head {
+            let mut hi = [0; ACCOUNT_ID_LEN];
+            hi[0] = 1;
+            ranges.push(([0; ACCOUNT_ID_LEN], Some(hi)));
+        }
+        ranges.sort_unstable();
+        let mut merged: Vec<IdRange> = Vec::new();
+        for (lo, hi) in ranges {
+            match merged.last_mut() {
+                Some((_, last)) if last.is_none_or(|last| lo <= last) => {
+                    *last = last.zip(hi).map(|(last, hi)| last.max(hi));
+                }
+                _ => merged.push((lo, hi)),
+            }
+        }
+        merged
+    }
+
+    fn may_match(&self, id: &[u8]) -> bool {
+        if self.zero_head && id[0] == 0 {
+            return true;
+        }
+        self.ranges
+            .iter()
+            .any(|(lo, hi)| id >= &lo[..] && hi.is_none_or(|hi| id < &hi[..]))
+    }
+}
+
+/// Account id ranges, producing addresses starting with `digits`, after `head`
+/// (format prefix without leading zeroes) is prepended
+fn id_ranges(head: &[u8], digits: &[u8]) -> Vec<IdRange> {
+    let mut value = BigUint::from(0u8);
+    for digit in digits {
+        let Some(digit) = ALPHABET.iter().position(|c| c == digit) else {
+            return Vec::new();
+        };
+        value = value * 58u8 + digit;
+    }
+    if digits[0] == b'1' {
+        // Only possible with zero leading byte, which is not filtered
+        return Vec::new();
+    }
+
+    let len = head.len() + ACCOUNT_ID_LEN + CHECKSUM_LEN;
+    let min = BigUint::from(1u8) << (8 * (len - 1));
+    let max = BigUint::from(1u8) << (8 * len);
+    let head_value = BigUint::from_bytes_be(head) << (8 * ACCOUNT_ID_LEN);
+    let id_max = BigUint::from(1u8) << (8 * ACCOUNT_ID_LEN);
+
+    let mut ranges = Vec::new();
+    let mut scale = BigUint::from(1u8);
+    // Address might be of any length, which fits `len` bytes
+    while &value * &scale < max {
+        let lo = (&value * &scale).max(min.clone());
+        let hi = (&value + 1u8) * &scale;
+        let hi = hi.min(max.clone());
+        scale *= 58u8;
+        if lo >= hi {
+            continue;
+        }
+        // Any checksum value is accepted
+        let lo = lo >> (8 * CHECKSUM_LEN);
+        let hi = (hi + 0xffffu32) >> (8 * CHECKSUM_LEN);
+        // Strip format prefix
+        if hi <= head_value || lo >= &head_value + &id_max {
+            continue;
+        }
+        let lo = if lo > head_value {
+            lo - &head_value
+        } else {
+            BigUint::from(0u8)
+        };
+        let hi = hi - &head_value;
+        ranges.push((
+            to_id_bytes(&lo).expect("lower than upper bound"),
+            to_id_bytes(&hi),
+        ));
+    }
+    ranges
+}
+
+/// Account ids, which may produce addresses with required prefixes in every format at once
+///
+/// Leading characters in every format are determined by the same account id, so prefixes
+/// may contradict each other, even if each of them is reachable. `None` if no matcher has
+/// prefix filter, i.e any id may match
+pub(crate) fn common_id_ranges(
+    matchers: &[Matcher],
+    scheme: Scheme,
+) -> Result<Option<Vec<IdRange>>, Error> {
+    let mut common: Option<Vec<IdRange>> = None;
+    let mut formats = Vec::new();
+    for matcher in matchers {
+        let Some(ranges) = matcher.id_ranges() else {
+            continue;
+        };
+        let ranges = match common {
+            Some(common) => intersect(&common, &ranges),
+            None => ranges,
+        };
+        if id_share(&ranges, scheme) == 0.0 {
+            return Err(Error::NeverMatches(
+                matcher.format,
+                format!(
+                    "no {} account id gives address with required prefix, together with the ones required in {}, as leading characters in every format are determined by the same account id",
+                    scheme,
+                    formats.join(", ")
+                ),
+            ));
+        }
+        common = Some(ranges);
+        formats.push(format_name(matcher.format));
+    }
+    Ok(common)
+}
+
+/// Ids, which are in both sets of non-overlapping ranges
+fn intersect(a: &[IdRange], b: &[IdRange]) -> Vec<IdRange> {
+    let mut out = Vec::new();
+    for (a_lo, a_hi) in a {
+        for (b_lo, b_hi) in b {
+            let lo = *a_lo.max(b_lo);
+            let hi = match (a_hi, b_hi) {
+                (Some(a_hi), Some(b_hi)) => Some(*a_hi.min(b_hi)),
+                _ => a_hi.or(*b_hi),
+            };
+            if hi.is_none_or(|hi| lo < hi) {
+                out.push((lo, hi));
+            }
+        }
+    }
+    out
+}
+
+/// Share of keys of `scheme`, account ids of which are within non-overlapping `ranges`
+pub(crate) fn id_share(ranges: &[IdRange], scheme: Scheme) -> f64 {
+    // Scaled to `0..1`
+    let fraction = |id: &[u8; ACCOUNT_ID_LEN]| {
+        id.iter()
+            .rev()
+            .fold(0.0, |acc, b| (acc + *b as f64) / 256.0)
+    };
+    ranges
+        .iter()
+        .map(|(lo, hi)| measure(fraction(lo), hi.as_ref().map_or(1.0, fraction), scheme))
+        .sum()
+}
+
+/// `None` if value doesn't fit
+fn to_id_bytes(value: &BigUint) -> Option<[u8; ACCOUNT_ID_LEN]> {
+    let bytes = value.to_bytes_be();
+    if bytes.len() > ACCOUNT_ID_LEN {
+        return None;
+    }
+    let mut out = [0; ACCOUNT_ID_LEN];
+    out[ACCOUNT_ID_LEN - bytes.len()..].copy_from_slice(&bytes);
+    Some(out)
+}
+
+/// Suffix, which is checked before encoding address
+///
+/// Last `k` characters of address are `X mod 58^k`, where `X = body * 2^16 + checksum`,
+/// and body is (format ‖ account id). For `k >= 3`, `58^k` exceeds checksum range,
+/// so most bodies are rejected without computing checksum at all, and for shorter
+/// suffixes only checksum is computed, without base58 encoding.
+#[derive(Clone)]
+struct SuffixFilter {
+    prefix: Vec<u8>,
+    /// Modulus (`58^k`) and expected remainder
+    suffixes: Vec<(u128, u128)>,
+}
+
+impl SuffixFilter {
+    /// `None` if pattern is not anchored at the end, or has no literal suffix
+    fn new(pattern: &str, format: u16) -> Option<Self> {
+        let literals = anchored_suffixes(pattern)?;
+        let mut suffixes = Vec::new();
+        for literal in literals {
+            if literal.is_empty() {
+                // Any address matches
+                return None;
+            }
+            let tail = &literal[literal.len().saturating_sub(MAX_SUFFIX_CHARS)..];
+            let mut value = 0u128;
+            let mut modulus = 1u128;
+            for digit in tail {
+                let Some(digit) = ALPHABET.iter().position(|c| c == digit) else {
+                    break;
+                };
+                value = value * 58 + digit as u128;
+                modulus *= 58;
+            }
+            if modulus == 58u128.pow(tail.len() as u32) {
+                suffixes.push((modulus, value));
+            }
+        }
+        Some(Self {
+            prefix: ss58_prefix(format),
+            suffixes,
+        })
+    }
+
+    fn may_match(&self, id: &[u8]) -> bool {
+        let mut checksum = None;
+        for (modulus, value) in &self.suffixes {
+            let body = self
+                .prefix
+                .iter()
+                .chain(id)
+                .fold(0u128, |acc, b| (acc * 256 + *b as u128) % modulus);
+            let shifted = body * (1 << 16) % modulus;
+            // Checksum should be equal to this, modulo `modulus`
+            let required = (value + modulus - shifted) % modulus;
+            if required >= 1 << 16 {
+                continue;
+            }
+            let checksum = *checksum.get_or_insert_with(|| self.checksum(id));
+            if (shifted + checksum) % modulus == *value {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn checksum(&self, id: &[u8]) -> u128 {
+        let mut data = Vec::with_capacity(7 + self.prefix.len() + id.len());
+        data.extend_from_slice(b"SS58PRE");
+        data.extend_from_slice(&self.prefix);
+        data.extend_from_slice(id);
+        let hash = blake2_512(&data);
+        u16::from_be_bytes([hash[0], hash[1]]) as u128
+    }
+}
+
+/// Literal suffixes of pattern, anchored at the end
+fn anchored_suffixes(pattern: &str) -> Option<Vec<Vec<u8>>> {
+    let hir = regex_syntax::parse(pattern).ok()?;
+    if !hir.properties().look_set_suffix().contains(Look::End) {
+        return None;
+    }
+    let seq = Extractor::new().kind(ExtractKind::Suffix).extract(&hir);
+    Some(
+        seq.literals()?
+            .iter()
+            .map(|literal| literal.as_bytes().to_vec())
+            .collect(),
+    )
+}
+
+/// Explain the cost of patterns, which constrain checksum-dominated tail of address
+fn checksum_warning(pattern: &str, suffix: Option<&SuffixFilter>) -> Option<String> {
+    let hir = regex_syntax::parse(pattern).ok()?;
+    if !hir.properties().look_set_suffix().contains(Look::End) {
+        return None;
+    }
+    let Some(suffix) = suffix else {
+        return Some(format!(
+            "pattern is anchored at the end, but last {} characters of ss58 address are dominated by blake2 checksum: they can't be steered, and every candidate has to be fully encoded to check them",
+            CHECKSUM_TAIL_CHARS
+        ));
+    };
+    let cheapest = suffix.suffixes.iter().map(|(modulus, _)| *modulus).min()?;
+    let chars = (cheapest as f64).log(58.0).round() as u32;
+    Some(format!(
+        "pattern suffix overlaps last {} of ss58 address, which are dominated by blake2 checksum and can't be steered: expected cost is ~{} attempts per match (58^{}{}), only checksums of candidates with matching body are computed",
+        match CHECKSUM_TAIL_CHARS.min(chars as usize) {
+            1 => "character".to_string(),
+            n => format!("{} characters", n),
+        },
+        cheapest / suffix.suffixes.len() as u128,
+        chars,
+        if suffix.suffixes.len() > 1 {
+            format!(" / {} alternatives", suffix.suffixes.len())
+        } else {
+            String::new()
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SeedSource;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const FORMATS: [u16; 5] = [0, 2, 42, 7391, 16383];
+
+    /// Patterns anchored on literal parts of addresses, so some candidates match them
+    fn patterns(addresses: &[String]) -> Vec<String> {
+        let mut patterns = Vec::new();
+        for pair in addresses.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            for n in 1..=4 {
+                patterns.push(format!("^{}", &a[..n]));
+                patterns.push(format!("{}$", &a[a.len() - n..]));
+                patterns.push(format!("^(?:{}|{})", &a[..n], &b[..n]));
+                patterns.push(format!("(?:{}|{})$", &a[a.len() - n..], &b[b.len() - n..]));
+                patterns.push(format!("^{}.*{}$", &a[..n], &a[a.len() - n..]));
+            }
+        }
+        patterns
+    }
+
+    /// Ids at both sides of every bound of prefix filter ranges
+    fn range_edges(matcher: &Matcher) -> Vec<[u8; ACCOUNT_ID_LEN]> {
+        let step = |id: [u8; ACCOUNT_ID_LEN], delta: i8| {
+            let value = BigUint::from_bytes_be(&id);
+            let value = match delta {
+                1 => value + 1u8,
+                _ if value == BigUint::from(0u8) => value,
+                _ => value - 1u8,
+            };
+            to_id_bytes(&value).unwrap_or([0xff; ACCOUNT_ID_LEN])
+        };
+        let mut edges = Vec::new();
+        for (lo, hi) in matcher.prefix.iter().flat_map(|p| &p.ranges) {
+            edges.extend([step(*lo, -1), *lo, step(*lo, 1)]);
+            if let Some(hi) = hi {
+                edges.extend([step(*hi, -1), *hi, step(*hi, 1)]);
+            }
+        }
+        edges
+    }
+
+    /// Filters should reject exactly the candidates, which regex doesn't match
+    fn assert_filters_agree(scheme: Scheme, format: u16, candidates: &[Account]) {
+        let sources = candidates
+            .iter()
+            .take(4)
+            .map(|a| a.address.clone())
+            .collect::<Vec<_>>();
+        let (mut prefixes, mut suffixes) = (0, 0);
+        for pattern in patterns(&sources) {
+            let format_ = AddressFormat::Ss58(format);
+            let matcher = match Matcher::new(&pattern, format_) {
+                Ok(matcher) => matcher,
+                Err(_) => continue,
+            };
+            prefixes += matcher.has_prefix_filter() as usize;
+            suffixes += matcher.has_suffix_filter() as usize;
+            // Ids are public keys for these schemes
+            let edges = match scheme {
+                Scheme::Ecdsa => Vec::new(),
+                _ => range_edges(&matcher)
+                    .into_iter()
+                    .map(|id| Account::from_public(scheme, format_, id.to_vec()))
+                    .collect(),
+            };
+            let mut hits = 0;
+            for candidate in candidates.iter().chain(&edges) {
+                let expected = matcher.regex().is_match(&candidate.address);
+                let found = matcher.is_match(&mut candidate.clone());
+                assert_eq!(
+                    found, expected,
+                    "{} {} {}: {}",
+                    scheme, format, pattern, candidate.address
+                );
+                hits += found as usize;
+            }
+            assert!(hits > 0, "{} {} {}", scheme, format, pattern);
+        }
+        assert!(prefixes > 0 && suffixes > 0, "{} {}", scheme, format);
+    }
+
+    #[test]
+    fn filters_agree_with_regex() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for &scheme in Scheme::ALL {
+            for format in FORMATS {
+                let format_ = AddressFormat::Ss58(format);
+                let mut candidates = (0..100)
+                    .map(|_| Account::generate(&mut rng, scheme, &SeedSource::Raw, format_))
+                    .collect::<Vec<_>>();
+                // Edges of account id range, i.e extra leading '1' for zero first byte
+                if scheme != Scheme::Ecdsa {
+                    for public in [[0; 32], [1; 32], [0xfe; 32], [0xff; 32]] {
+                        candidates.push(Account::from_public(scheme, format_, public.to_vec()));
+                    }
+                }
+                assert_filters_agree(scheme, format, &candidates);
+            }
+        }
+    }
+
+    #[test]
+    fn common_prefixes_allow_matching_ids() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for &scheme in Scheme::ALL {
+            for _ in 0..20 {
+                let mut account =
+                    Account::generate(&mut rng, scheme, &SeedSource::Raw, AddressFormat::Ss58(0));
+                for n in 1..=3 {
+                    let matchers = FORMATS.map(|format| {
+                        let format = AddressFormat::Ss58(format);
+                        let address = account.address_in(format);
+                        Matcher::new(&format!("^{}", &address[..n]), format).unwrap()
+                    });
+                    let common = common_id_ranges(&matchers, scheme).unwrap().unwrap();
+                    let id: [u8; ACCOUNT_ID_LEN] = account.account_id().into();
+                    assert!(
+                        common
+                            .iter()
+                            .any(|(lo, hi)| id >= *lo && hi.is_none_or(|hi| id < hi)),
+                        "{} {}",
+                        scheme,
+                        account.address
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn contradicting_prefixes() {
+        let matchers = [
+            Matcher::new("^1[ab]", AddressFormat::Ss58(0)).unwrap(),
+            Matcher::new("^[CDEFGHJ]a", AddressFormat::Ss58(2)).unwrap(),
+        ];
+        for &scheme in Scheme::ALL {
+            assert!(matches!(
+                common_id_ranges(&matchers, scheme),
+                Err(Error::NeverMatches(AddressFormat::Ss58(2), _))
+            ));
+        }
+    }
+}
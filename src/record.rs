@@ -0,0 +1,417 @@
+use crate::{Account, Scheme};
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::{AccountId32, Ss58Codec};
+
+/// Found account in machine-readable form, see [`Account`] for meaning of fields
+///
+/// Byte strings are 0x-prefixed hex
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// Index of match, starting from 1
+    pub index: usize,
+    pub address: String,
+    pub format: String,
+    pub scheme: String,
+    pub public: String,
+    /// Bytes, which are encoded in address, see [`crate::AddressFormat::account_id`]
+    pub account_id: String,
+    pub seed: Option<String>,
+    pub phrase: Option<String>,
+    pub secret_key: Option<String>,
+    pub path: Option<String>,
+    pub offset: Option<String>,
+    /// Addresses in other requested formats, as pairs of format and address
+    pub other_addresses: Vec<(String, String)>,
+    pub matched: Option<String>,
+    /// Name of matched pattern
+    pub pattern: Option<String>,
+    /// Regexes, which addresses have matched, as pairs of format and regex
+    #[serde(default)]
+    pub regexes: Vec<(String, String)>,
+    pub word: Option<String>,
+    /// Character index of word in address
+    pub position: Option<usize>,
+    /// Attempts made by search, when account was found
+    pub attempts: Option<u64>,
+    /// Unix time in seconds, when account was found
+    pub timestamp: Option<u64>,
+    /// Path of exported keystore file, secrets are omitted then
+    pub keystore: Option<String>,
+}
+
+impl Record {
+    /// Columns of [`Record::to_csv`], in the same order
+    pub const CSV_HEADER: &'static str = concat!(
+        "index,address,format,scheme,public,account_id,",
+        "seed,phrase,secret_key,path,offset,",
+        "other_addresses,matched,pattern,regexes,word,position,",
+        "attempts,timestamp,keystore",
+    );
+
+    /// Attempts, timestamp and keystore are left empty
+    pub fn new(index: usize, account: &Account) -> Self {
+        let word = account.word.as_ref();
+        Self {
+            index,
+            address: account.address.clone(),
+            format: account.format.to_string(),
+            scheme: account.scheme.to_string(),
+            public: to_hex(&account.public),
+            account_id: to_hex(&account.format.account_id(account.scheme, &account.public)),
+            seed: account.seed.as_ref().map(|s| to_hex(s)),
+            phrase: account.phrase.clone(),
+            secret_key: account.secret_key.as_ref().map(|s| to_hex(s)),
+            path: account.path.clone(),
+            offset: account.offset.as_ref().map(|o| to_hex(o)),
+            other_addresses: account
+                .other_addresses
+                .iter()
+                .map(|(format, address)| (format.to_string(), address.clone()))
+                .collect(),
+            matched: account.matched.clone(),
+            pattern: account.pattern.clone(),
+            regexes: account
+                .regexes
+                .iter()
+                .map(|(format, regex)| (format.to_string(), regex.clone()))
+                .collect(),
+            word: word.map(|(word, _)| word.clone()),
+            position: word.map(|(_, position)| *position),
+            attempts: None,
+            timestamp: None,
+            keystore: None,
+        }
+    }
+
+    /// Comma separated fields, quoted where needed
+    ///
+    /// Other addresses and regexes are written as `format=value` pairs, separated by `;`.
+    /// `;` and `\` in values, i.e in regexes, are escaped by `\`
+    pub fn to_csv(&self) -> String {
+        let pairs = |pairs: &[(String, String)]| {
+            pairs
+                .iter()
+                .map(|(format, value)| {
+                    let value = value.replace('\\', "\\\\").replace(';', "\\;");
+                    format!("{}={}", format, value)
+                })
+                .collect::<Vec<_>>()
+                .join(";")
+        };
+        let fields = [
+            Some(self.index.to_string()),
+            Some(self.address.clone()),
+            Some(self.format.clone()),
+            Some(self.scheme.clone()),
+            Some(self.public.clone()),
+            Some(self.account_id.clone()),
+            self.seed.clone(),
+            self.phrase.clone(),
+            self.secret_key.clone(),
+            self.path.clone(),
+            self.offset.clone(),
+            Some(pairs(&self.other_addresses)),
+            self.matched.clone(),
+            self.pattern.clone(),
+            Some(pairs(&self.regexes)),
+            self.word.clone(),
+            self.position.map(|p| p.to_string()),
+            self.attempts.map(|a| a.to_string()),
+            self.timestamp.map(|t| t.to_string()),
+            self.keystore.clone(),
+        ];
+        fields
+            .iter()
+            .map(|field| csv_field(field.as_deref().unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Parse results printed by search, output format is detected from content
+    ///
+    /// Returns records together with their line numbers. Lines which aren't results,
+    /// i.e progress events, are skipped. Text output has no public key and account id,
+    /// they are left empty
+    pub fn parse_results(results: &str) -> Vec<(usize, Result<Record, String>)> {
+        let mut lines = results
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+        let Some((_, first)) = lines.peek().copied() else {
+            return Vec::new();
+        };
+        if first.starts_with("index,") {
+            let header = split_csv(first);
+            return lines
+                // Header is repeated, when outputs of several runs are concatenated
+                .filter(|(_, line)| *line != first)
+                .map(|(n, line)| (n, Self::from_csv(&header, line)))
+                .collect();
+        }
+        lines
+            .filter_map(|(n, line)| {
+                let record = if line.starts_with(['{', '[', ',']) {
+                    Self::from_json(line)
+                } else {
+                    Self::from_text(line)
+                };
+                Some((n, record?))
+            })
+            .collect()
+    }
+
+    /// Element of json array or ndjson line, `None` for array brackets and events
+    fn from_json(line: &str) -> Option<Result<Self, String>> {
+        let json = line.trim_start_matches(['[', ',']).trim_end_matches(']');
+        if json.is_empty() {
+            return None;
+        }
+        let value: serde_json::Value = match serde_json::from_str(json) {
+            Ok(value) => value,
+            Err(e) => return Some(Err(e.to_string())),
+        };
+        if value.get("event").is_some() {
+            return None;
+        }
+        Some(serde_json::from_value(value).map_err(|e| e.to_string()))
+    }
+
+    fn from_csv(header: &[String], line: &str) -> Result<Self, String> {
+        let fields = split_csv(line);
+        if fields.len() != header.len() {
+            return Err(format!(
+                "expected {} fields, found {}",
+                header.len(),
+                fields.len()
+            ));
+        }
+        let get = |name: &str| {
+            header
+                .iter()
+                .position(|h| h == name)
+                .map(|i| fields[i].clone())
+                .filter(|field| !field.is_empty())
+        };
+        let required = |name: &str| get(name).ok_or_else(|| format!("{} is missing", name));
+        let number = |name: &str| {
+            get(name)
+                .map(|n| n.parse().map_err(|_| format!("{} is not a number", name)))
+                .transpose()
+        };
+        let pairs = |name: &str| {
+            let mut pairs = Vec::new();
+            for pair in get(name).iter().flat_map(|o| split_pairs(o)) {
+                let (format, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected format=value in {}: {}", name, pair))?;
+                pairs.push((format.to_string(), value.to_string()));
+            }
+            Ok::<_, String>(pairs)
+        };
+        Ok(Self {
+            index: number("index")?.ok_or("index is missing")? as usize,
+            address: required("address")?,
+            format: required("format")?,
+            scheme: required("scheme")?,
+            public: get("public").unwrap_or_default(),
+            account_id: get("account_id").unwrap_or_default(),
+            seed: get("seed"),
+            phrase: get("phrase"),
+            secret_key: get("secret_key"),
+            path: get("path"),
+            offset: get("offset"),
+            other_addresses: pairs("other_addresses")?,
+            matched: get("matched"),
+            pattern: get("pattern"),
+            regexes: pairs("regexes")?,
+            word: get("word"),
+            position: number("position")?.map(|p| p as usize),
+            attempts: number("attempts")?,
+            timestamp: number("timestamp")?,
+            keystore: get("keystore"),
+        })
+    }
+
+    /// Line printed for [`Account`] or leaderboard entry, `None` if line isn't a result
+    fn from_text(line: &str) -> Option<Result<Self, String>> {
+        let (index, rest) = line.split_once(". ")?;
+        let index = index.parse().ok()?;
+        // Leaderboard entry is prefixed by score and its metrics
+        let rest = match rest.strip_prefix("score ") {
+            Some(scored) => scored.split_once("): ")?.1,
+            None => rest,
+        };
+        Some(Self::parse_text(index, rest))
+    }
+
+    fn parse_text(index: usize, line: &str) -> Result<Self, String> {
+        let mut addresses = Vec::new();
+        let mut fields: Vec<String> = Vec::new();
+        let mut scheme = None;
+        for part in line.split(", ") {
+            if scheme.is_some() {
+                match fields.last_mut() {
+                    // Quoted value, i.e regex, may contain the separator
+                    Some(last) if last.matches('"').count() % 2 == 1 => {
+                        last.push_str(", ");
+                        last.push_str(part);
+                    }
+                    _ => fields.push(part.to_string()),
+                }
+                continue;
+            }
+            // The first field follows scheme after a space
+            let (name, first) = part.split_once(' ').unwrap_or((part, ""));
+            match name.parse::<Scheme>() {
+                Ok(parsed) => {
+                    scheme = Some(parsed);
+                    if !first.is_empty() {
+                        fields.push(first.to_string());
+                    }
+                }
+                Err(_) => addresses.push(part),
+            }
+        }
+        let scheme = scheme.ok_or("scheme is missing")?;
+        // Pairs of format and address, as in `other_addresses`
+        let mut addresses = addresses
+            .into_iter()
+            .map(|part| match part.split_once(" (") {
+                Some((address, format)) => format
+                    .strip_suffix(')')
+                    .map(|format| (format.to_string(), address.to_string()))
+                    .ok_or_else(|| format!("malformed address: {}", part)),
+                None => infer_format(part).map(|format| (format, part.to_string())),
+            });
+        let (format, address) = addresses.next().ok_or("address is missing")??;
+        let mut record = Self {
+            index,
+            address,
+            format,
+            scheme: scheme.to_string(),
+            public: String::new(),
+            account_id: String::new(),
+            seed: None,
+            phrase: None,
+            secret_key: None,
+            path: None,
+            offset: None,
+            other_addresses: addresses.collect::<Result<_, _>>()?,
+            matched: None,
+            pattern: None,
+            regexes: Vec::new(),
+            word: None,
+            position: None,
+            attempts: None,
+            timestamp: None,
+            keystore: None,
+        };
+        for field in fields {
+            let (key, value) = field
+                .split_once(" = ")
+                .ok_or_else(|| format!("expected key = value: {}", field))?;
+            let value = value.to_string();
+            match key {
+                "seed" => record.seed = Some(value),
+                // Ethereum private key is printed without 0x
+                "private key" => record.seed = Some(format!("0x{}", value)),
+                "secret key" => record.secret_key = Some(value),
+                "offset" => record.offset = Some(value),
+                "path" => record.path = Some(value),
+                "phrase" => record.phrase = Some(unquote(&value)?),
+                "matched" => record.matched = Some(unquote(&value)?),
+                "pattern" => record.pattern = Some(value),
+                "regex" => record
+                    .regexes
+                    .push((record.format.clone(), unquote(&value)?)),
+                "word" => {
+                    let (word, position) = value
+                        .rsplit_once(" at ")
+                        .ok_or_else(|| format!("malformed word: {}", value))?;
+                    record.word = Some(unquote(word)?);
+                    record.position = Some(
+                        position
+                            .parse()
+                            .map_err(|_| format!("malformed word position: {}", position))?,
+                    );
+                }
+                "keystore" => record.keystore = Some(value),
+                _ => match key
+                    .strip_prefix("regex (")
+                    .and_then(|format| format.strip_suffix(')'))
+                {
+                    Some(format) => record.regexes.push((format.to_string(), unquote(&value)?)),
+                    None => return Err(format!("unknown field: {}", key)),
+                },
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// Format of address, printed without one
+fn infer_format(address: &str) -> Result<String, String> {
+    if address.starts_with("0x") {
+        return Ok("h160".to_string());
+    }
+    AccountId32::from_ss58check_with_version(address)
+        .map(|(_, format)| format.prefix().to_string())
+        .map_err(|e| format!("invalid address {}: {:?}", address, e))
+}
+
+fn unquote(value: &str) -> Result<String, String> {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .map(str::to_string)
+        .ok_or_else(|| format!("expected quoted value: {}", value))
+}
+
+/// Pairs of csv field, separated by `;` not escaped by `\`
+fn split_pairs(field: &str) -> Vec<String> {
+    let mut pairs = vec![String::new()];
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        let pair = pairs.last_mut().expect("there is always a pair");
+        match c {
+            '\\' => pair.extend(chars.next()),
+            ';' => pairs.push(String::new()),
+            _ => pair.push(c),
+        }
+    }
+    pairs
+}
+
+/// Fields of csv line, as written by [`Record::to_csv`]
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("there is always a field");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
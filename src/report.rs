@@ -0,0 +1,396 @@
+use crate::{Account, Error, Estimate, Keystore, Progress, Record, Search};
+use std::fmt::{self, Display};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often speed is reported, while there are no matches
+const STATS_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How matches are printed by [`Reporter`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable lines, see [`Account`] display
+    #[default]
+    Text,
+    /// Array of [`Record`]s
+    Json,
+    /// Record per line
+    Ndjson,
+    /// Record per line, with [`Record::CSV_HEADER`]
+    Csv,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [
+        OutputFormat::Text,
+        OutputFormat::Json,
+        OutputFormat::Ndjson,
+        OutputFormat::Csv,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+        }
+    }
+}
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OutputFormat::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "unknown output format: {}, expected text, json, ndjson or csv",
+                    s
+                )
+            })
+    }
+}
+
+/// Directory, where secrets of new matches are saved as keystore files
+pub struct Export {
+    dir: PathBuf,
+    passphrase: String,
+}
+
+impl Export {
+    /// Directory should exist, keystores are encrypted with `passphrase`
+    pub fn new(dir: impl Into<PathBuf>, passphrase: impl Into<String>) -> Self {
+        Self {
+            dir: dir.into(),
+            passphrase: passphrase.into(),
+        }
+    }
+
+    /// Keystore file of account, named by its address
+    pub fn path(&self, account: &Account) -> PathBuf {
+        self.dir.join(format!("{}.json", account.address))
+    }
+
+    /// Keystore is named by matched pattern or word
+    pub fn save(&self, account: &Account) -> Result<PathBuf, Error> {
+        let name = account
+            .pattern
+            .clone()
+            .or_else(|| account.word.as_ref().map(|(word, _)| word.clone()))
+            .unwrap_or_else(|| "fancy address".to_string());
+        let keystore = Keystore::new(account, &self.passphrase, name, unix_time() * 1000)?;
+        let path = self.path(account);
+        fs::write(&path, keystore.to_json())?;
+        Ok(path)
+    }
+}
+
+/// Prints matches to stdout, and everything else to stderr, in requested format
+pub struct Reporter {
+    format: OutputFormat,
+    /// Matches printed so far, including the ones found by previous runs
+    reported: usize,
+    export: Option<Export>,
+}
+
+impl Reporter {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            reported: 0,
+            export: None,
+        }
+    }
+
+    /// Save secrets of new matches to keystore files, instead of printing them
+    pub fn export(mut self, export: Export) -> Self {
+        self.export = Some(export);
+        self
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+    pub fn reported(&self) -> usize {
+        self.reported
+    }
+
+    /// `attempts` is `None` for matches, found by previous runs
+    ///
+    /// New matches are exported to keystore, if requested, their secrets are not printed then
+    pub fn report(&mut self, account: &Account, attempts: Option<u64>) {
+        let exported = match &self.export {
+            Some(export) if attempts.is_some() => match export.save(account) {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    // Secrets are printed instead, so the match is not lost
+                    let path = export.path(account);
+                    self.notice("warning", format!("can't save {}: {}", path.display(), e));
+                    None
+                }
+            },
+            Some(export) => Some(export.path(account)).filter(|path| path.exists()),
+            None => None,
+        };
+        let stripped;
+        let account = match exported {
+            Some(_) => {
+                stripped = Account {
+                    seed: None,
+                    phrase: None,
+                    secret_key: None,
+                    ..account.clone()
+                };
+                &stripped
+            }
+            None => account,
+        };
+        let started = self.reported > 0;
+        self.reported += 1;
+        let index = self.reported;
+        let mut record = Record::new(index, account);
+        if attempts.is_some() {
+            record.attempts = attempts;
+            record.timestamp = Some(unix_time());
+        }
+        record.keystore = exported.as_ref().map(|path| path.display().to_string());
+        match self.format {
+            OutputFormat::Text => match &record.keystore {
+                Some(keystore) => println!("{}. {}, keystore = {}", index, account, keystore),
+                None => println!("{}. {}", index, account),
+            },
+            OutputFormat::Json => println!(
+                "{}{}",
+                if started { "," } else { "[" },
+                serde_json::to_string(&record).expect("record is serializable")
+            ),
+            OutputFormat::Ndjson => println!(
+                "{}",
+                serde_json::to_string(&record).expect("record is serializable")
+            ),
+            OutputFormat::Csv => {
+                if !started {
+                    println!("{}", Record::CSV_HEADER);
+                }
+                println!("{}", record.to_csv());
+            }
+        }
+    }
+
+    /// Print matches, found before the checkpoint was saved
+    pub fn resume(&mut self, progress: &Progress) {
+        let checkpoint = progress.checkpoint();
+        for matched in &checkpoint.matches {
+            self.report(matched, None);
+        }
+        if checkpoint.attempts > 0 {
+            self.notice(
+                "resumed",
+                format!(
+                    "after {} attempts in {}",
+                    human_count(checkpoint.attempts as f64),
+                    human_duration(checkpoint.elapsed.as_secs_f64())
+                ),
+            );
+        }
+    }
+
+    /// Report matches of search until `limit` of them are reported, including resumed ones,
+    /// or until workers stop
+    ///
+    /// Estimates are reported first, and speed every few seconds. Progress is saved on every
+    /// match, and once in its interval
+    pub fn run(&mut self, search: &Search, limit: usize, mut progress: Option<&mut Progress>) {
+        self.estimates(search);
+        while self.reported < limit {
+            let mut received = match search.recv_timeout(STATS_INTERVAL) {
+                Ok(matched) => vec![matched],
+                // Workers have stopped, i.e quotas of named patterns are filled
+                Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => Vec::new(),
+            };
+            let save = progress
+                .as_ref()
+                .is_some_and(|p| !received.is_empty() || p.is_due());
+            // Matches of paths before the index are in the channel already, take them all
+            let derivation_index = search.derivation_index();
+            if save {
+                received.extend(search.matches().try_iter());
+            }
+            for matched in received {
+                if self.reported >= limit {
+                    break;
+                }
+                if let Some(progress) = progress.as_deref_mut() {
+                    if !progress.add(matched.clone()) {
+                        continue;
+                    }
+                }
+                self.report(&matched, Some(search.attempts()));
+            }
+            if let Some(progress) = progress.as_deref_mut().filter(|_| save) {
+                self.save(progress, search, derivation_index);
+            }
+            self.speed(search, limit);
+        }
+
+        if let Some(progress) = progress {
+            self.save(progress, search, search.derivation_index());
+        }
+        self.finish();
+    }
+
+    fn estimates(&self, search: &Search) {
+        if search.pattern_names().is_empty() {
+            if let Some(estimate) = search.estimate() {
+                self.estimate(None, estimate);
+            }
+        } else {
+            for (name, estimate) in search.pattern_names().iter().zip(search.estimates()) {
+                if let Some(estimate) = estimate {
+                    self.estimate(Some(name), *estimate);
+                }
+            }
+        }
+    }
+
+    fn speed(&self, search: &Search, limit: usize) {
+        let total_attempts = search.attempts();
+        let elapsed_secs = search.elapsed().as_secs();
+        let Some(attempts_per_second) = total_attempts.checked_div(elapsed_secs) else {
+            return;
+        };
+        let matches_found = self.reported;
+        // Quotas of named patterns are filled at different rates
+        let eta = search
+            .estimate()
+            .filter(|_| search.pattern_names().is_empty())
+            .and_then(|estimate| estimate.eta(limit - matches_found, attempts_per_second));
+        if self.format == OutputFormat::Text {
+            eprintln!(
+                "{} attempts per second, {:.6} matches per second. {:.10}% of total matched: {}/{}{}",
+                attempts_per_second,
+                matches_found as f64 / elapsed_secs as f64,
+                matches_found as f64 / total_attempts as f64,
+                matches_found,
+                limit,
+                eta.map(|secs| format!(", eta {}", human_duration(secs)))
+                    .unwrap_or_default(),
+            )
+        }
+        self.event(serde_json::json!({
+            "event": "progress",
+            "attempts": total_attempts,
+            "elapsed_secs": search.elapsed().as_secs_f64(),
+            "attempts_per_second": attempts_per_second,
+            "matches": matches_found,
+            "limit": limit,
+            "eta_secs": eta,
+        }));
+    }
+
+    fn save(&self, progress: &mut Progress, search: &Search, derivation_index: u64) {
+        if let Err(e) = progress.save(search, derivation_index) {
+            self.notice(
+                "warning",
+                format!("can't save {}: {}", progress.path().display(), e),
+            );
+        }
+    }
+
+    /// Close json array, or print csv header if there were no matches
+    pub fn finish(&self) {
+        match self.format {
+            OutputFormat::Json => println!("{}", if self.reported > 0 { "]" } else { "[]" }),
+            OutputFormat::Csv if self.reported == 0 => println!("{}", Record::CSV_HEADER),
+            _ => {}
+        }
+    }
+
+    /// Human-readable message, prefixed by event name
+    pub fn notice(&self, event: &str, message: impl Display) {
+        match self.format {
+            OutputFormat::Text => eprintln!("{}: {}", event, message),
+            _ => self.event(serde_json::json!({
+                "event": event,
+                "message": message.to_string(),
+            })),
+        }
+    }
+
+    /// Machine-readable event, ignored in text format
+    pub fn event(&self, event: serde_json::Value) {
+        if self.format != OutputFormat::Text {
+            eprintln!("{}", event);
+        }
+    }
+
+    fn estimate(&self, pattern: Option<&str>, estimate: Estimate) {
+        match (self.format, pattern) {
+            (OutputFormat::Text, Some(pattern)) => eprintln!("estimate: {}: {}", pattern, estimate),
+            (OutputFormat::Text, None) => eprintln!(
+                "estimate: {}, {} attempts per match",
+                estimate,
+                human_count(estimate.attempts())
+            ),
+            _ => self.event(serde_json::json!({
+                "event": "estimate",
+                "pattern": pattern,
+                "probability": estimate.probability(),
+                "attempts": estimate.attempts(),
+            })),
+        }
+    }
+}
+
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|t| t.as_secs())
+        .unwrap_or_default()
+}
+
+/// Integer below a million, or a number in scientific notation
+pub fn human_count(count: f64) -> String {
+    if count < 1e6 {
+        format!("{:.0}", count)
+    } else if count.is_finite() {
+        format!("{:.2e}", count)
+    } else {
+        "infinite".to_string()
+    }
+}
+
+/// Duration in the largest fitting unit, up to years
+pub fn human_duration(secs: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.25 * DAY;
+    if !secs.is_finite() {
+        "forever".to_string()
+    } else if secs < MINUTE {
+        format!("{:.1}s", secs)
+    } else if secs < HOUR {
+        format!("{:.0}m {:.0}s", (secs / MINUTE).floor(), secs % MINUTE)
+    } else if secs < DAY {
+        format!("{:.1} hours", secs / HOUR)
+    } else if secs < YEAR {
+        format!("{:.1} days", secs / DAY)
+    } else if secs < 1e6 * YEAR {
+        format!("{:.1} years", secs / YEAR)
+    } else {
+        format!("{:.2e} years", secs / YEAR)
+    }
+}
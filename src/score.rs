@@ -0,0 +1,263 @@
+use crate::Account;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+/// Used, when no dictionary is specified
+const DEFAULT_DICTIONARY: &[&str] = &[
+    "ace", "age", "air", "all", "ant", "ape", "art", "axe", "bad", "bag", "bat", "bee", "big",
+    "bit", "box", "bug", "bus", "cab", "can", "cap", "car", "cat", "cow", "cup", "cut", "day",
+    "den", "dew", "dog", "dot", "dry", "ear", "eat", "egg", "elf", "elk", "end", "eye", "fan",
+    "far", "fax", "fig", "fit", "fix", "fly", "fog", "fox", "fun", "fur", "gas", "gem", "gig",
+    "gum", "gym", "hat", "hex", "hip", "hot", "hub", "ice", "ink", "jam", "jar", "jaw", "jet",
+    "joy", "key", "kid", "kit", "lab", "lake", "lava", "law", "leaf", "lime", "lion", "log",
+    "love", "map", "max", "mix", "moon", "mud", "nap", "net", "new", "nut", "oak", "owl", "pay",
+    "pen", "pet", "pie", "pig", "pin", "pop", "pub", "rain", "ray", "red", "rib", "run", "sea",
+    "sky", "sun", "tax", "tea", "ten", "tiger", "top", "toy", "van", "web", "wet", "win", "wolf",
+    "yes", "zap", "zen", "zero", "zoo", "bear", "bird", "coin", "cool", "cute", "dark", "dash",
+    "deer", "fancy", "fast", "fire", "fish", "gold", "good", "hero", "king", "lucky", "magic",
+    "ninja", "nova", "pixel", "queen", "rich", "rock", "safe", "star", "storm", "vault", "wave",
+    "wise", "word", "yak",
+];
+
+/// Measured features of address, in number of characters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Longest run of the same character, beyond the first one
+    pub run: usize,
+    /// Longest ascending or descending sequence, i.e `1234` or `fedc`, beyond the first character
+    pub sequence: usize,
+    /// Longest palindrome, beyond the first character
+    pub palindrome: usize,
+    /// Characters, covered by dictionary words, case-insensitively
+    pub words: usize,
+    /// Longest stretch of alternating consonants and vowels, beyond the first letter
+    pub pronounceable: usize,
+}
+
+impl Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "run {}, sequence {}, palindrome {}, words {}, pronounceable {}",
+            self.run, self.sequence, self.palindrome, self.words, self.pronounceable
+        )
+    }
+}
+
+/// Weights of metrics in score
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weights {
+    pub run: f64,
+    pub sequence: f64,
+    pub palindrome: f64,
+    pub words: f64,
+    pub pronounceable: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            run: 1.0,
+            sequence: 1.0,
+            palindrome: 0.5,
+            words: 1.0,
+            pronounceable: 0.5,
+        }
+    }
+}
+
+impl Weights {
+    pub fn score(&self, metrics: &Metrics) -> f64 {
+        self.run * metrics.run as f64
+            + self.sequence * metrics.sequence as f64
+            + self.palindrome * metrics.palindrome as f64
+            + self.words * metrics.words as f64
+            + self.pronounceable * metrics.pronounceable as f64
+    }
+}
+
+impl FromStr for Weights {
+    type Err = String;
+
+    /// Comma separated overrides of default weights, i.e `run=2,words=0`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Self::default();
+        for entry in s.split(',').filter(|e| !e.is_empty()) {
+            let (name, weight) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid weight: {}, expected i.e run=2", entry))?;
+            let weight = weight
+                .parse()
+                .map_err(|_| format!("weight is not a number: {}", weight))?;
+            match name {
+                "run" => weights.run = weight,
+                "sequence" => weights.sequence = weight,
+                "palindrome" => weights.palindrome = weight,
+                "words" => weights.words = weight,
+                "pronounceable" => weights.pronounceable = weight,
+                _ => return Err(format!(
+                    "unknown metric: {}, expected run, sequence, palindrome, words or pronounceable",
+                    name
+                )),
+            }
+        }
+        Ok(weights)
+    }
+}
+
+/// Scores addresses by how attractive they look
+#[derive(Clone)]
+pub struct Scorer {
+    weights: Weights,
+    /// Lowercase words
+    dictionary: Arc<[String]>,
+    /// Leading characters, which are the same for all addresses of the format, are not scored
+    skip: usize,
+}
+
+impl Default for Scorer {
+    fn default() -> Self {
+        Self::new(Weights::default())
+    }
+}
+
+impl Scorer {
+    /// Scorer with built-in dictionary
+    pub fn new(weights: Weights) -> Self {
+        Self {
+            weights,
+            dictionary: DEFAULT_DICTIONARY.iter().map(|w| w.to_string()).collect(),
+            skip: 1,
+        }
+    }
+    /// Replace built-in dictionary, words are matched case-insensitively
+    pub fn dictionary(mut self, words: impl IntoIterator<Item = String>) -> Self {
+        self.dictionary = words
+            .into_iter()
+            .map(|w| w.to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+        self
+    }
+    pub(crate) fn skip_leading(mut self, chars: usize) -> Self {
+        self.skip = chars;
+        self
+    }
+    pub fn weights(&self) -> &Weights {
+        &self.weights
+    }
+
+    pub fn score(&self, address: &str) -> f64 {
+        self.weights.score(&self.metrics(address))
+    }
+
+    pub fn metrics(&self, address: &str) -> Metrics {
+        let chars = &address.as_bytes()[self.skip.min(address.len())..];
+        Metrics {
+            run: longest(chars, |a, b| a == b),
+            sequence: longest(chars, |a, b| b == a + 1).max(longest(chars, |a, b| a == b + 1)),
+            palindrome: palindrome(chars).saturating_sub(1),
+            words: self.words(chars),
+            pronounceable: longest(chars, |a, b| {
+                a.is_ascii_alphabetic() && b.is_ascii_alphabetic() && is_vowel(a) != is_vowel(b)
+            }),
+        }
+    }
+
+    fn words(&self, chars: &[u8]) -> usize {
+        let lower = chars.to_ascii_lowercase();
+        let mut covered = vec![false; lower.len()];
+        for word in self.dictionary.iter() {
+            let word = word.as_bytes();
+            for start in 0..lower.len().saturating_sub(word.len() - 1) {
+                if &lower[start..start + word.len()] == word {
+                    covered[start..start + word.len()].fill(true);
+                }
+            }
+        }
+        covered.iter().filter(|c| **c).count()
+    }
+}
+
+/// Longest chain of characters, every neighbour pair of which is `linked`, minus one
+fn longest(chars: &[u8], linked: impl Fn(u8, u8) -> bool) -> usize {
+    let mut best = 0;
+    let mut current = 0;
+    for pair in chars.windows(2) {
+        if linked(pair[0], pair[1]) {
+            current += 1;
+            best = best.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    best
+}
+
+fn palindrome(chars: &[u8]) -> usize {
+    let mut best = chars.len().min(1);
+    for center in 0..chars.len() {
+        // Odd and even length palindromes
+        for (mut lo, mut hi) in [(center, center), (center, center + 1)] {
+            while hi < chars.len() && chars[lo] == chars[hi] {
+                best = best.max(hi - lo + 1);
+                if lo == 0 {
+                    break;
+                }
+                lo -= 1;
+                hi += 1;
+            }
+        }
+    }
+    best
+}
+
+fn is_vowel(c: u8) -> bool {
+    b"aeiouyAEIOUY".contains(&c)
+}
+
+/// Best scored accounts, shared between worker threads
+pub(crate) struct Leaderboard {
+    size: usize,
+    /// Lowest score on the full board, as f64 bits
+    threshold: AtomicU64,
+    entries: Mutex<Vec<(f64, Account)>>,
+}
+
+impl Leaderboard {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            size: size.max(1),
+            threshold: AtomicU64::new(f64::NEG_INFINITY.to_bits()),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Whether account with this score would get on the board
+    pub(crate) fn qualifies(&self, score: f64) -> bool {
+        score > f64::from_bits(self.threshold.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn offer(&self, score: f64, account: Account) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.size && score <= entries[entries.len() - 1].0 {
+            return;
+        }
+        let position = entries.partition_point(|(s, _)| *s >= score);
+        entries.insert(position, (score, account));
+        entries.truncate(self.size);
+        if entries.len() == self.size {
+            self.threshold
+                .store(entries[entries.len() - 1].0.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Best accounts first
+    pub(crate) fn entries(&self) -> Vec<(f64, Account)> {
+        self.entries.lock().unwrap().clone()
+    }
+}
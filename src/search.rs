@@ -0,0 +1,735 @@
+use crate::{
+    account::address_matches,
+    estimate,
+    matcher::{common_id_ranges, id_share},
+    Account, AddressFormat, Checkpoint, Derivation, Error, Estimate, Incremental, Leaderboard,
+    Matcher, Scheme, Scorer, SeedSource, SplitKey, Substitutions, Wordlist,
+};
+use rand::thread_rng;
+use regex::RegexSet;
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    Arc,
+};
+use std::{
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// How many accounts should be generated before matching all of them
+const THREAD_BATCH_SIZE: usize = 5000;
+/// Batch size for sources, which spend milliseconds per candidate, i.e running PBKDF2,
+/// so progress is reported and workers stop quickly
+const SLOW_BATCH_SIZE: usize = 50;
+
+/// Which patterns should match, when candidates are checked in several network formats
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Require {
+    /// Address in every format should match its pattern
+    #[default]
+    All,
+    /// At least one address should match
+    Any,
+}
+
+/// Named pattern with its own quota, see [`SearchBuilder::with_patterns`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    pub name: String,
+    pub regex: String,
+    /// How many matches are needed
+    pub quota: u64,
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    /// Whitespace separated name, regex and optional quota, which defaults to 1
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let (Some(name), Some(regex)) = (parts.next(), parts.next()) else {
+            return Err(format!("expected name, regex and optional quota: {}", s));
+        };
+        let quota = match parts.next() {
+            Some(quota) => quota
+                .parse()
+                .map_err(|_| format!("quota is not a number: {}", quota))?,
+            None => 1,
+        };
+        if let Some(extra) = parts.next() {
+            return Err(format!("unexpected {} after quota", extra));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            regex: regex.to_string(),
+            quota,
+        })
+    }
+}
+
+/// Named patterns, which are checked at once, each until its quota is filled
+#[derive(Clone)]
+struct PatternSet {
+    set: RegexSet,
+    names: Arc<[String]>,
+    remaining: Arc<[AtomicU64]>,
+}
+
+impl PatternSet {
+    /// `matchers` are compiled patterns of the set, in the same order
+    fn is_match(
+        &self,
+        account: &mut Account,
+        matchers: &[Matcher],
+        known: &HashSet<String>,
+    ) -> bool {
+        let format = matchers[0].format();
+        let address = account.address_in(format);
+        let mut hits = self.set.matches(address).into_iter().collect::<Vec<_>>();
+        if format == AddressFormat::H160 {
+            hits.extend(&self.set.matches(&address.to_lowercase()));
+            hits.sort_unstable();
+            hits.dedup();
+        }
+        // Quota is not spent on matches, which won't be reported
+        if hits.is_empty() || known.contains(address) {
+            return false;
+        }
+        for hit in hits {
+            let reserved = self.remaining[hit]
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |r| r.checked_sub(1))
+                .is_ok();
+            if reserved {
+                // Saves matched variant, if requested
+                matchers[hit].is_match(account);
+                account.pattern = Some(self.names[hit].clone());
+                let regex = matchers[hit].regex().as_str().to_string();
+                account.regexes.push((format, regex));
+                return true;
+            }
+        }
+        false
+    }
+
+    fn is_done(&self) -> bool {
+        self.remaining
+            .iter()
+            .all(|r| r.load(Ordering::Relaxed) == 0)
+    }
+}
+
+/// Matches are scored and kept on leaderboard, instead of being sent to the channel
+#[derive(Clone)]
+struct Scoring {
+    scorer: Scorer,
+    leaderboard: Arc<Leaderboard>,
+}
+
+/// Patterns for every requested format, or named patterns for the only format
+#[derive(Clone)]
+struct Targets {
+    matchers: Vec<Matcher>,
+    require: Require,
+    patterns: Option<PatternSet>,
+    /// Address in the first format should also contain a word
+    words: Option<Wordlist>,
+    scoring: Option<Scoring>,
+    /// Addresses of matches, which were found before resuming
+    known: Arc<HashSet<String>>,
+}
+
+impl Targets {
+    /// Matched account has addresses in all formats encoded
+    fn is_match(&self, account: &mut Account) -> bool {
+        if let Some(patterns) = &self.patterns {
+            return patterns.is_match(account, &self.matchers, &self.known);
+        }
+        let matched = match self.require {
+            Require::All => self.matchers.iter().all(|m| m.is_match(account)),
+            Require::Any => self.matchers.iter().any(|m| m.is_match(account)),
+        } && self.words.as_ref().is_none_or(|w| w.is_match(account));
+        if matched {
+            for matcher in &self.matchers {
+                let (format, regex) = (matcher.format(), matcher.regex());
+                // Only some regexes have matched with `Require::Any`
+                let hit = address_matches(format, account.address_in(format), regex);
+                // Regex matching empty string matches any address, it's not worth recording
+                if hit && !regex.is_match("") {
+                    account.regexes.push((format, regex.as_str().to_string()));
+                }
+            }
+            // Some formats could be encoded by matchers out of order
+            account.other_addresses.sort_by_key(|(format, _)| {
+                self.matchers.iter().position(|m| m.format() == *format)
+            });
+        }
+        matched && !self.known.contains(&account.address)
+    }
+}
+
+fn worker_thread(
+    tx: Sender<Account>,
+    attempts: Arc<AtomicU64>,
+    kill_pill: Receiver<()>,
+    targets: Targets,
+    scheme: Scheme,
+    source: SeedSource,
+    addr_type: AddressFormat,
+) {
+    let mut thread_rng = thread_rng();
+    let batch_size = match source {
+        SeedSource::Mnemonic(..) | SeedSource::Derive(_) => SLOW_BATCH_SIZE,
+        _ => THREAD_BATCH_SIZE,
+    };
+    let mut accounts = Vec::with_capacity(batch_size);
+    let mut incremental = matches!(source, SeedSource::Incremental).then(Incremental::default);
+
+    loop {
+        // Claimed batch is released only after its matches are sent, so it can be resumed
+        let mut claimed = None;
+        if let Some(incremental) = &mut incremental {
+            incremental.fill(&mut thread_rng, &mut accounts, batch_size, addr_type);
+        } else if let SeedSource::Derive(derivation) = &source {
+            let start = derivation.claim(batch_size as u64);
+            accounts
+                .extend((start..start + batch_size as u64).map(|i| derivation.nth(i, addr_type)));
+            claimed = Some((derivation, start));
+        } else {
+            for _ in 0..batch_size {
+                accounts.push(Account::candidate(
+                    &mut thread_rng,
+                    scheme,
+                    &source,
+                    addr_type,
+                ));
+            }
+        }
+        let mut best_of_batch: Option<(f64, Account)> = None;
+        // Rest of incremental batch is skipped after a match
+        let mut checked = 0;
+        for mut account in accounts.drain(..) {
+            checked += 1;
+            if !targets.is_match(&mut account) {
+                continue;
+            }
+            if let Some(scoring) = &targets.scoring {
+                let score = scoring.scorer.score(&account.address);
+                if !scoring.leaderboard.qualifies(score) {
+                    continue;
+                }
+                if incremental.is_none() {
+                    scoring.leaderboard.offer(score, account);
+                } else if best_of_batch.as_ref().is_none_or(|(best, _)| score > *best) {
+                    // Secrets of the same batch are related, only the best one gets on leaderboard
+                    best_of_batch = Some((score, account));
+                }
+                continue;
+            }
+            // Match is reported with attempts, which have found it
+            attempts.fetch_add(std::mem::take(&mut checked), Ordering::Relaxed);
+            if tx.send(account).is_err() {
+                // Nobody is listening for matches anymore
+                return;
+            }
+            if incremental.is_some() {
+                // Secrets of the same batch are related, only one of them can be used
+                break;
+            }
+        }
+        attempts.fetch_add(checked, Ordering::Relaxed);
+        if let (Some(scoring), Some((score, account))) = (&targets.scoring, best_of_batch) {
+            scoring.leaderboard.offer(score, account);
+        }
+        if let Some((derivation, start)) = claimed {
+            derivation.release(start);
+        }
+        if targets.patterns.as_ref().is_some_and(PatternSet::is_done) {
+            break;
+        }
+        match kill_pill.try_recv() {
+            Ok(_) | Err(TryRecvError::Disconnected) => {
+                break;
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+}
+
+/// Search parameters, call [`SearchBuilder::spawn`] to start searching
+pub struct SearchBuilder {
+    regex: String,
+    scheme: Scheme,
+    source: SeedSource,
+    format: AddressFormat,
+    other_formats: Vec<(AddressFormat, String)>,
+    patterns: Vec<Pattern>,
+    require: Require,
+    ignore_case: bool,
+    substitutions: Option<Substitutions>,
+    words: Option<Vec<String>>,
+    score: Option<(Scorer, usize)>,
+    threads: u8,
+    base: Option<Base>,
+    resumed: Resumed,
+    known: HashSet<String>,
+}
+
+/// Progress of a previous run, see [`SearchBuilder::resume`]
+#[derive(Clone, Copy, Default)]
+struct Resumed {
+    attempts: u64,
+    elapsed: Duration,
+    derivation_index: u64,
+}
+
+/// Key, from which candidates are derived instead of random seeds
+enum Base {
+    Secret { suri: String, template: String },
+    Public { public: String, template: String },
+    SplitKey(String),
+}
+
+impl SearchBuilder {
+    /// Search for addresses matching regex
+    pub fn new(regex: impl Into<String>) -> Self {
+        Self {
+            regex: regex.into(),
+            scheme: Scheme::default(),
+            source: SeedSource::default(),
+            format: AddressFormat::Ss58(42),
+            other_formats: Vec::new(),
+            patterns: Vec::new(),
+            require: Require::default(),
+            ignore_case: false,
+            substitutions: None,
+            words: None,
+            score: None,
+            threads: 1,
+            base: None,
+            resumed: Resumed::default(),
+            known: HashSet::new(),
+        }
+    }
+    /// Search for several named patterns at once, each until its quota is filled
+    ///
+    /// Matches are tagged by [`Account::pattern`], only one network format is supported
+    pub fn with_patterns(patterns: impl IntoIterator<Item = Pattern>) -> Self {
+        Self {
+            patterns: patterns.into_iter().collect(),
+            ..Self::new("")
+        }
+    }
+    /// Key scheme of generated accounts, defaults to sr25519
+    pub fn scheme(mut self, scheme: Scheme) -> Self {
+        self.scheme = scheme;
+        self
+    }
+    /// How candidate seeds are produced, defaults to raw random seeds
+    pub fn seed_source(mut self, source: SeedSource) -> Self {
+        self.source = source;
+        self
+    }
+    /// Instead of generating random seeds, enumerate derivation paths of `suri`
+    ///
+    /// See [`Derivation::new`] for accepted values
+    pub fn derive_from(mut self, suri: impl Into<String>, template: impl Into<String>) -> Self {
+        self.base = Some(Base::Secret {
+            suri: suri.into(),
+            template: template.into(),
+        });
+        self
+    }
+    /// Enumerate soft derivation paths of sr25519 public key, no secret is needed
+    ///
+    /// See [`Derivation::from_public`] for accepted values
+    pub fn derive_from_public(
+        mut self,
+        public: impl Into<String>,
+        template: impl Into<String>,
+    ) -> Self {
+        self.base = Some(Base::Public {
+            public: public.into(),
+            template: template.into(),
+        });
+        self
+    }
+    /// Search for offset to requester's sr25519 public key, see [`SplitKey`]
+    pub fn split_key(mut self, public: impl Into<String>) -> Self {
+        self.base = Some(Base::SplitKey(public.into()));
+        self
+    }
+    /// Network address format, defaults to 42 (generic substrate)
+    pub fn format(mut self, format: impl Into<AddressFormat>) -> Self {
+        self.format = format.into();
+        self
+    }
+    /// Also check address of the same key in another network format against `regex`
+    ///
+    /// Matches have addresses in all formats, see [`Account::other_addresses`]
+    pub fn other_format(
+        mut self,
+        format: impl Into<AddressFormat>,
+        regex: impl Into<String>,
+    ) -> Self {
+        self.other_formats.push((format.into(), regex.into()));
+        self
+    }
+    /// Whether all or any of the formats should match, defaults to all
+    pub fn require(mut self, require: Require) -> Self {
+        self.require = require;
+        self
+    }
+    /// Match letters in any case, matched variant is saved to [`Account::matched`]
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+    /// Treat patterns as plain words, letters of which may be replaced by look-alikes
+    ///
+    /// See [`Substitutions::pattern`], matched variant is saved to [`Account::matched`]
+    pub fn lookalike(mut self, substitutions: Substitutions) -> Self {
+        self.substitutions = Some(substitutions);
+        self
+    }
+    /// Address should also contain any of `words` after its leading characters
+    ///
+    /// Words are matched in any case with [`SearchBuilder::ignore_case`], found word is saved to
+    /// [`Account::word`], see [`Wordlist`]
+    pub fn wordlist(mut self, words: impl IntoIterator<Item = String>) -> Self {
+        self.words = Some(words.into_iter().collect());
+        self
+    }
+    /// Instead of reporting matches, keep `top` of them with the best score
+    ///
+    /// Search runs until stopped, see [`Search::leaderboard`]
+    pub fn score(mut self, scorer: Scorer, top: usize) -> Self {
+        self.score = Some((scorer, top));
+        self
+    }
+    /// How many worker threads to use, defaults to 1
+    pub fn threads(mut self, threads: u8) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Continue counting attempts and elapsed time of a previous run, saved to checkpoint
+    ///
+    /// Derivation search continues from its saved index, see [`Search::derivation_index`].
+    /// It checks some paths again, and their matches, which are saved already, aren't reported,
+    /// so they don't spend quotas of named patterns. Quotas should be reduced by saved matches,
+    /// see [`Checkpoint::remaining`]
+    pub fn resume(mut self, checkpoint: &Checkpoint) -> Self {
+        self.resumed = Resumed {
+            attempts: checkpoint.attempts,
+            elapsed: checkpoint.elapsed,
+            derivation_index: checkpoint.derivation_index,
+        };
+        self.known = checkpoint
+            .matches
+            .iter()
+            .map(|m| m.address.clone())
+            .collect();
+        self
+    }
+
+    /// Start worker threads
+    pub fn spawn(self) -> Result<Search, Error> {
+        let targets = if self.patterns.is_empty() {
+            std::iter::once((&self.regex, self.format))
+                .chain(self.other_formats.iter().map(|(f, r)| (r, *f)))
+                .collect::<Vec<_>>()
+        } else {
+            if !self.other_formats.is_empty() {
+                return Err(Error::InvalidPatterns(
+                    "named patterns can only be checked in one format".to_string(),
+                ));
+            }
+            if self.score.is_some() {
+                return Err(Error::InvalidPatterns(
+                    "named patterns can't be combined with scoring".to_string(),
+                ));
+            }
+            if self.words.is_some() {
+                return Err(Error::InvalidPatterns(
+                    "named patterns can't be combined with wordlist".to_string(),
+                ));
+            }
+            for (i, pattern) in self.patterns.iter().enumerate() {
+                if self.patterns[..i].iter().any(|p| p.name == pattern.name) {
+                    return Err(Error::InvalidPatterns(format!(
+                        "duplicate pattern name: {}",
+                        pattern.name
+                    )));
+                }
+            }
+            self.patterns
+                .iter()
+                .map(|p| (&p.regex, self.format))
+                .collect()
+        };
+        let mut matchers = Vec::new();
+        let mut estimates = Vec::new();
+        for (regex, format) in targets {
+            if format.is_reserved() {
+                return Err(Error::ReservedFormat(format));
+            }
+            if !format.supports(self.scheme) {
+                return Err(Error::UnsupportedFormat(self.scheme, format));
+            }
+            let regex = &match &self.substitutions {
+                Some(substitutions) => substitutions.pattern(regex, self.ignore_case)?,
+                None if self.ignore_case => format!("(?i){}", regex),
+                None => regex.clone(),
+            };
+            let mut matcher = Matcher::new(regex, format)?;
+            if self.ignore_case || self.substitutions.is_some() {
+                matcher = matcher.report_matched();
+            }
+            matcher.check_scheme(self.scheme)?;
+            matchers.push(matcher);
+            estimates.push(estimate(regex, format, self.scheme));
+        }
+        // Prefixes in different formats constrain the same account id
+        let common = match self.require {
+            Require::All if self.patterns.is_empty() => common_id_ranges(&matchers, self.scheme)?,
+            _ => None,
+        };
+        let words = match self.words {
+            Some(words) => Some(Wordlist::new(words, self.format, self.ignore_case)?),
+            None => None,
+        };
+        let estimate = estimates
+            .iter()
+            .copied()
+            .collect::<Option<Vec<_>>>()
+            .map(|estimates| match (self.require, &common) {
+                // Candidate is reported, if it matches any of named patterns
+                _ if !self.patterns.is_empty() => Estimate::any(estimates),
+                (Require::All, Some(common)) => {
+                    let shares = matchers.iter().map(|m| {
+                        m.id_ranges()
+                            .map_or(1.0, |ranges| id_share(&ranges, self.scheme))
+                    });
+                    Estimate::all_within(
+                        estimates.into_iter().zip(shares),
+                        id_share(common, self.scheme),
+                    )
+                }
+                (Require::All, None) => Estimate::all(estimates),
+                (Require::Any, _) => Estimate::any(estimates),
+            })
+            .map(|estimate| match &words {
+                Some(words) => Estimate::all([estimate, words.estimate()]),
+                None => estimate,
+            });
+        let patterns = if self.patterns.is_empty() {
+            None
+        } else {
+            Some(PatternSet {
+                set: RegexSet::new(matchers.iter().map(|m| m.regex().as_str()))?,
+                names: self.patterns.iter().map(|p| p.name.clone()).collect(),
+                remaining: self
+                    .patterns
+                    .iter()
+                    .map(|p| AtomicU64::new(p.quota))
+                    .collect(),
+            })
+        };
+        let scoring = self.score.map(|(scorer, top)| Scoring {
+            scorer: scorer.skip_leading(self.format.fixed_chars()),
+            leaderboard: Arc::new(Leaderboard::new(top)),
+        });
+        let targets = Targets {
+            matchers,
+            require: self.require,
+            patterns,
+            words,
+            scoring,
+            known: Arc::new(self.known),
+        };
+        let source = match &self.base {
+            Some(Base::Secret { suri, template }) => SeedSource::Derive(Arc::new(
+                Derivation::new(self.scheme, suri, template)?
+                    .start_at(self.resumed.derivation_index),
+            )),
+            Some(Base::Public { public, template }) => {
+                if self.scheme != Scheme::Sr25519 {
+                    return Err(Error::Sr25519Only(self.scheme, "public key derivation"));
+                }
+                SeedSource::Derive(Arc::new(
+                    Derivation::from_public(public, template)?
+                        .start_at(self.resumed.derivation_index),
+                ))
+            }
+            Some(Base::SplitKey(public)) => {
+                if self.scheme != Scheme::Sr25519 {
+                    return Err(Error::Sr25519Only(self.scheme, "split-key search"));
+                }
+                SeedSource::SplitKey(SplitKey::new(public)?)
+            }
+            None => self.source.clone(),
+        };
+        if matches!(source, SeedSource::Incremental) && self.scheme != Scheme::Sr25519 {
+            return Err(Error::Sr25519Only(self.scheme, "incremental search"));
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let derivation = match &source {
+            SeedSource::Derive(derivation) => Some(derivation.clone()),
+            _ => None,
+        };
+        let attempts = Arc::new(AtomicU64::new(self.resumed.attempts));
+        let mut children = Vec::new();
+        let mut kill_pills = Vec::new();
+        for _ in 0..self.threads {
+            let thread_tx = tx.clone();
+            let thread_attempts = attempts.clone();
+            let thread_targets = targets.clone();
+            let scheme = self.scheme;
+            let source = source.clone();
+            let format = self.format;
+            let (kill_pill_tx, kill_pill_rx) = mpsc::channel();
+            let child = thread::spawn(move || {
+                worker_thread(
+                    thread_tx,
+                    thread_attempts,
+                    kill_pill_rx,
+                    thread_targets,
+                    scheme,
+                    source,
+                    format,
+                )
+            });
+            kill_pills.push(kill_pill_tx);
+            children.push(child);
+        }
+
+        Ok(Search {
+            pattern_names: targets.patterns.map(|p| p.names),
+            words: targets.words,
+            scoring: targets.scoring,
+            matchers: targets.matchers,
+            estimates,
+            estimate,
+            matches: rx,
+            attempts,
+            start_time: Instant::now(),
+            resumed_elapsed: self.resumed.elapsed,
+            derivation,
+            kill_pills,
+            children,
+        })
+    }
+}
+
+/// Running search
+///
+/// Workers are stopped once this handle is dropped
+pub struct Search {
+    matchers: Vec<Matcher>,
+    pattern_names: Option<Arc<[String]>>,
+    words: Option<Wordlist>,
+    scoring: Option<Scoring>,
+    estimates: Vec<Option<Estimate>>,
+    estimate: Option<Estimate>,
+    matches: Receiver<Account>,
+    attempts: Arc<AtomicU64>,
+    start_time: Instant,
+    /// Time spent by previous runs
+    resumed_elapsed: Duration,
+    derivation: Option<Arc<Derivation>>,
+    kill_pills: Vec<Sender<()>>,
+    children: Vec<JoinHandle<()>>,
+}
+
+impl Search {
+    /// Compiled patterns for every format, see [`Matcher::warnings`]
+    pub fn matchers(&self) -> &[Matcher] {
+        &self.matchers
+    }
+    /// Words, which address should contain, see [`SearchBuilder::wordlist`]
+    pub fn wordlist(&self) -> Option<&Wordlist> {
+        self.words.as_ref()
+    }
+    /// Names of patterns, see [`SearchBuilder::with_patterns`], empty for a single regex
+    pub fn pattern_names(&self) -> &[String] {
+        self.pattern_names.as_deref().unwrap_or_default()
+    }
+    /// Estimates for every matcher, in the same order
+    pub fn estimates(&self) -> &[Option<Estimate>] {
+        &self.estimates
+    }
+    /// Probability of candidate to match, `None` if some pattern is too complex to analyse
+    pub fn estimate(&self) -> Option<Estimate> {
+        self.estimate
+    }
+    /// Channel of found matches
+    pub fn matches(&self) -> &Receiver<Account> {
+        &self.matches
+    }
+    /// Blocking iterator over found matches
+    pub fn iter(&self) -> mpsc::Iter<'_, Account> {
+        self.matches.iter()
+    }
+    /// Wait for next match for at most `timeout`
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Account, RecvTimeoutError> {
+        self.matches.recv_timeout(timeout)
+    }
+
+    /// Scorer used in scoring mode, see [`SearchBuilder::score`]
+    pub fn scorer(&self) -> Option<&Scorer> {
+        self.scoring.as_ref().map(|s| &s.scorer)
+    }
+    /// Best scored matches so far, best first, empty if not in scoring mode
+    pub fn leaderboard(&self) -> Vec<(f64, Account)> {
+        self.scoring
+            .as_ref()
+            .map(|s| s.leaderboard.entries())
+            .unwrap_or_default()
+    }
+
+    /// How many accounts were checked so far
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+    /// Time since the search was started, including previous runs
+    pub fn elapsed(&self) -> Duration {
+        self.resumed_elapsed + self.start_time.elapsed()
+    }
+    /// Index, from which derivation search should be resumed, see [`Derivation::checked`]
+    ///
+    /// Matches of paths before it are already in [`Search::matches`] channel.
+    /// Zero for other sources
+    pub fn derivation_index(&self) -> u64 {
+        self.derivation.as_ref().map_or(0, |d| d.checked())
+    }
+
+    /// Ask workers to stop, they will finish their current batch first
+    pub fn cancel(&self) {
+        for pill in &self.kill_pills {
+            // Worker might be already gone
+            let _ = pill.send(());
+        }
+    }
+    /// Stop workers and wait for them to exit
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.cancel();
+        for child in self.children.drain(..) {
+            // Panic of a worker is already printed, and panicking again in drop would abort
+            let _ = child.join();
+        }
+    }
+}
+
+impl Drop for Search {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
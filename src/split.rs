@@ -0,0 +1,104 @@
+use crate::{derive::parse_sr25519_public, Account, AddressFormat, Error, Scheme};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_TABLE, ristretto::CompressedRistretto, scalar::Scalar,
+};
+use rand::RngCore;
+use schnorrkel::SecretKey;
+use sp_core::{sr25519, Pair};
+
+/// Split-key (outsourced) search
+///
+/// Requester shares only its sr25519 public key `P`, worker searches for scalar
+/// offset `k`, such that `P + k*B` has matching address. Offset is useless
+/// without requester's secret `s`, final secret key `s + k` is then computed
+/// locally using [`combine`].
+#[derive(Clone)]
+pub struct SplitKey {
+    base: curve25519_dalek::ristretto::RistrettoPoint,
+}
+
+impl SplitKey {
+    /// Accepts sr25519 public key as ss58 address or hex
+    pub fn new(public: &str) -> Result<Self, Error> {
+        let base = parse_sr25519_public(public)
+            .and_then(|public| CompressedRistretto(public.0).decompress())
+            .ok_or_else(|| Error::InvalidPublicKey(public.to_owned()))?;
+        Ok(Self { base })
+    }
+
+    /// Try random offset, address is not encoded
+    pub(crate) fn generate<R: RngCore>(&self, rng: &mut R, format: AddressFormat) -> Account {
+        let mut wide = [0; 64];
+        rng.fill_bytes(&mut wide);
+        let offset = Scalar::from_bytes_mod_order_wide(&wide);
+
+        let public = (self.base + &offset * &RISTRETTO_BASEPOINT_TABLE).compress();
+        Account {
+            offset: Some(offset.to_bytes()),
+            ..Account::unencoded(Scheme::Sr25519, format, public.0.to_vec())
+        }
+    }
+}
+
+/// Add offset, found by split-key search, to requester's secret
+///
+/// `suri` is requester's secret, as accepted by `subkey`. Resulting account
+/// has no seed, only expanded secret key.
+pub fn combine(suri: &str, offset: &[u8; 32], format: AddressFormat) -> Result<Account, Error> {
+    let pair = sr25519::Pair::from_string(suri, None).map_err(Error::InvalidSecretUri)?;
+    let offset = Scalar::from_canonical_bytes(*offset).ok_or(Error::InvalidOffset)?;
+
+    let secret = schnorrkel::Keypair::from(pair).secret.to_bytes();
+    let mut key = [0; 32];
+    key.copy_from_slice(&secret[..32]);
+    let key = Scalar::from_canonical_bytes(key).expect("schnorrkel keeps scalar canonical");
+
+    let mut secret_key = [0; 64];
+    secret_key[..32].copy_from_slice((key + offset).as_bytes());
+    // Nonce is kept from the requester's key
+    secret_key[32..].copy_from_slice(&secret[32..]);
+
+    let public = SecretKey::from_bytes(&secret_key)
+        .expect("scalar is canonical")
+        .to_public();
+    Ok(Account {
+        secret_key: Some(secret_key),
+        ..Account::from_public(Scheme::Sr25519, format, public.to_bytes().to_vec())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn combined_secret_derives_found_key() {
+        let format = AddressFormat::Ss58(42);
+        for suri in [
+            "//Alice",
+            "bottom drive obey lake curtain smoke basket hold race lonely fit walk//hard/soft",
+        ] {
+            let public = sr25519::Pair::from_string(suri, None).unwrap().public();
+            let split = SplitKey::new(&hex::encode(public.0)).unwrap();
+            for _ in 0..16 {
+                let found = split.generate(&mut thread_rng(), format).encoded();
+                let offset = found.offset.expect("offset is set");
+                let combined = combine(suri, &offset, format).unwrap();
+                assert_eq!(combined.public, found.public);
+                assert_eq!(combined.address, found.address);
+                let secret_key = combined.secret_key.expect("secret key is set");
+                let pair = sr25519::Pair::from_seed_slice(&secret_key).unwrap();
+                assert_eq!(pair.public().0.as_slice(), found.public.as_slice());
+            }
+        }
+    }
+
+    #[test]
+    fn non_canonical_offset() {
+        assert!(matches!(
+            combine("//Alice", &[0xff; 32], AddressFormat::Ss58(42)),
+            Err(Error::InvalidOffset)
+        ));
+    }
+}
@@ -0,0 +1,441 @@
+use crate::{
+    account::address_matches, analysis::format_name, Account, AddressFormat, Error, Language,
+    Pattern, Record, Scheme,
+};
+use regex::Regex;
+use secp256k1::PublicKey;
+use sp_core::crypto::{AccountId32, Ss58Codec};
+
+/// Re-derives found accounts from their secrets, and checks they still match their patterns
+pub struct Verifier {
+    language: Language,
+    regexes: Vec<Regex>,
+    patterns: Vec<(String, Regex)>,
+}
+
+/// Result of [`Verifier::check`]
+#[derive(Default, Debug)]
+pub struct Verdict {
+    /// Inconsistencies of record, empty if it's valid
+    pub problems: Vec<String>,
+    /// Why secret wasn't checked, only public parts of record are checked then
+    pub unverified: Option<&'static str>,
+    /// Record has no regex, pattern or word, which address should match, and none was given
+    pub pattern_unchecked: bool,
+}
+
+impl Verifier {
+    /// Phrases are parsed using wordlist of `language`
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            regexes: Vec::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Any address of every record should match any of regexes
+    pub fn regexes(mut self, regexes: &[String], ignore_case: bool) -> Result<Self, Error> {
+        for regex in regexes {
+            self.regexes.push(compile(regex, ignore_case)?);
+        }
+        Ok(self)
+    }
+
+    /// Records, found by named pattern, should match its regex
+    pub fn patterns(mut self, patterns: &[Pattern], ignore_case: bool) -> Result<Self, Error> {
+        for pattern in patterns {
+            let regex = compile(&pattern.regex, ignore_case)?;
+            self.patterns.push((pattern.name.clone(), regex));
+        }
+        Ok(self)
+    }
+
+    /// Check record against its secret, address format and patterns
+    ///
+    /// Regexes, recorded by search, are always checked, in addition to the given ones
+    pub fn check(&self, record: &Record) -> Verdict {
+        let mut verdict = Verdict {
+            pattern_unchecked: record.regexes.is_empty()
+                && record.word.is_none()
+                && self.regexes.is_empty()
+                && (record.pattern.is_none() || self.patterns.is_empty()),
+            ..Verdict::default()
+        };
+        match self.check_into(record, &mut verdict.problems) {
+            Ok(unverified) => verdict.unverified = unverified,
+            Err(problem) => verdict.problems.push(problem),
+        }
+        verdict
+    }
+
+    /// Returns why secret wasn't checked, or the problem, which prevents further checks
+    fn check_into(
+        &self,
+        record: &Record,
+        problems: &mut Vec<String>,
+    ) -> Result<Option<&'static str>, String> {
+        let scheme: Scheme = record.scheme.parse()?;
+        let mut addresses = vec![(
+            record.format.parse::<AddressFormat>()?,
+            record.address.clone(),
+        )];
+        for (format, address) in &record.other_addresses {
+            addresses.push((format.parse()?, address.clone()));
+        }
+        for (format, address) in &addresses {
+            if !format.supports(scheme) {
+                return Err(Error::UnsupportedFormat(scheme, *format).to_string());
+            }
+            if let Err(problem) = check_format(*format, address) {
+                problems.push(problem);
+            }
+        }
+        let format = addresses[0].0;
+
+        let secret = record
+            .phrase
+            .as_ref()
+            .or(record.seed.as_ref())
+            .or(record.secret_key.as_ref());
+        let mut unverified = None;
+        let account = match secret {
+            Some(secret) => {
+                let account = Account::from_secret(scheme, format, secret, self.language)
+                    .map_err(|e| e.to_string())?;
+                if let (Some(seed), Some(derived)) = (&record.seed, &account.seed) {
+                    if !hex_eq(seed, derived) {
+                        problems.push("seed is not derived from phrase".to_string());
+                    }
+                }
+                Some(account)
+            }
+            None => {
+                unverified = Some(if record.offset.is_some() {
+                    "split-key offset should be combined with requester's secret"
+                } else if record.keystore.is_some() {
+                    "secret was exported to keystore"
+                } else {
+                    "no secret is recorded"
+                });
+                recorded_public(record, scheme, format)?
+                    .map(|public| Account::from_public(scheme, format, public))
+            }
+        };
+        if let Some(mut account) = account {
+            if !record.public.is_empty() && !hex_eq(&record.public, &account.public) {
+                problems.push(format!("public key is 0x{}", hex::encode(&account.public)));
+            }
+            let account_id = account.format.account_id(account.scheme, &account.public);
+            if !record.account_id.is_empty() && !hex_eq(&record.account_id, &account_id) {
+                problems.push(format!("account id is 0x{}", hex::encode(account_id)));
+            }
+            for (format, address) in &addresses {
+                let derived = account.address_in(*format);
+                if derived != address {
+                    problems.push(format!(
+                        "{} address is {}, not {}",
+                        format_name(*format),
+                        derived,
+                        address
+                    ));
+                }
+            }
+        }
+
+        let matches = |regex: &Regex| {
+            addresses
+                .iter()
+                .any(|(format, address)| address_matches(*format, address, regex))
+        };
+        if !self.regexes.is_empty() && !self.regexes.iter().any(matches) {
+            problems.push("address doesn't match any regex".to_string());
+        }
+        for (format, regex) in &record.regexes {
+            let format: AddressFormat = format.parse()?;
+            let Some((_, address)) = addresses.iter().find(|(f, _)| *f == format) else {
+                problems.push(format!("no {} address for regex", format_name(format)));
+                continue;
+            };
+            match Regex::new(regex) {
+                Ok(compiled) if address_matches(format, address, &compiled) => {}
+                Ok(_) => {
+                    problems.push(format!("address {} doesn't match regex {}", address, regex))
+                }
+                Err(e) => problems.push(format!("invalid regex {}: {}", regex, e)),
+            }
+        }
+        if let (Some(name), false) = (&record.pattern, self.patterns.is_empty()) {
+            match self.patterns.iter().find(|(n, _)| n == name) {
+                Some((_, regex)) if !matches(regex) => {
+                    problems.push(format!("address doesn't match pattern {}", name))
+                }
+                Some(_) => {}
+                None => problems.push(format!("unknown pattern {}", name)),
+            }
+        }
+        if let Some(matched) = &record.matched {
+            let found = addresses.iter().any(|(format, address)| {
+                address.contains(matched.as_str())
+                    || (*format == AddressFormat::H160
+                        && address.to_lowercase().contains(matched.as_str()))
+            });
+            if !found {
+                problems.push(format!("address doesn't contain matched \"{}\"", matched));
+            }
+        }
+        if let Some(word) = &record.word {
+            let found = record
+                .position
+                .and_then(|position| record.address.get(position..position + word.len()))
+                .is_some_and(|found| found.eq_ignore_ascii_case(word));
+            if !found {
+                problems.push(format!("word \"{}\" is not at recorded position", word));
+            }
+        }
+        Ok(unverified)
+    }
+}
+
+fn compile(regex: &str, ignore_case: bool) -> Result<Regex, Error> {
+    Ok(match ignore_case {
+        true => Regex::new(&format!("(?i){}", regex))?,
+        false => Regex::new(regex)?,
+    })
+}
+
+/// Whether address is well-formed, and has network prefix of format
+fn check_format(format: AddressFormat, address: &str) -> Result<(), String> {
+    match format {
+        AddressFormat::Ss58(expected) => {
+            let (_, found) = AccountId32::from_ss58check_with_version(address)
+                .map_err(|e| format!("invalid address {}: {:?}", address, e))?;
+            if found.prefix() != expected {
+                return Err(format!(
+                    "address {} has ss58 prefix {}, not {}",
+                    address,
+                    found.prefix(),
+                    expected
+                ));
+            }
+        }
+        AddressFormat::H160 => {
+            let valid = address
+                .strip_prefix("0x")
+                .is_some_and(|hex| hex.len() == 40 && hex.bytes().all(|c| c.is_ascii_hexdigit()));
+            if !valid {
+                return Err(format!("invalid h160 address {}", address));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recorded public key, or the one encoded in ss58 address, if they are the same
+fn recorded_public(
+    record: &Record,
+    scheme: Scheme,
+    format: AddressFormat,
+) -> Result<Option<Vec<u8>>, String> {
+    if !record.public.is_empty() {
+        let public = decode_hex(&record.public)?;
+        // Compressed ecdsa key has a parity byte
+        let expected = match scheme {
+            Scheme::Sr25519 | Scheme::Ed25519 => 32,
+            Scheme::Ecdsa => 33,
+        };
+        if public.len() != expected {
+            return Err(format!(
+                "{} public key should be {} bytes long, not {}",
+                scheme,
+                expected,
+                public.len()
+            ));
+        }
+        // H160 address is computed from the point, which should be valid
+        if scheme == Scheme::Ecdsa && PublicKey::from_slice(&public).is_err() {
+            return Err(format!("invalid ecdsa public key {}", record.public));
+        }
+        return Ok(Some(public));
+    }
+    Ok(match (scheme, format) {
+        (Scheme::Sr25519 | Scheme::Ed25519, AddressFormat::Ss58(_)) => {
+            AccountId32::from_ss58check_with_version(&record.address)
+                .ok()
+                .map(|(id, _)| AsRef::<[u8]>::as_ref(&id).to_vec())
+        }
+        // Account id of ecdsa key is a hash of public key
+        _ => None,
+    })
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    hex::decode(s.trim_start_matches("0x")).map_err(|e| format!("malformed hex {}: {}", s, e))
+}
+
+fn hex_eq(recorded: &str, bytes: &[u8]) -> bool {
+    decode_hex(recorded).is_ok_and(|recorded| recorded == bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MnemonicType, SeedSource};
+    use rand::thread_rng;
+
+    /// Account printed in every output format, parsed back and checked
+    fn check_printed(account: &Account, keystore: Option<&str>) -> Vec<Verdict> {
+        let mut record = Record::new(1, account);
+        record.keystore = keystore.map(str::to_string);
+        let text = match keystore {
+            Some(keystore) => format!("1. {}, keystore = {}", account, keystore),
+            None => format!("1. {}", account),
+        };
+        let outputs = [
+            text,
+            serde_json::to_string(&record).unwrap(),
+            format!("{}\n{}", Record::CSV_HEADER, record.to_csv()),
+        ];
+        let verifier = Verifier::new(Language::English);
+        outputs
+            .iter()
+            .map(|output| {
+                let mut records = Record::parse_results(output);
+                assert_eq!(records.len(), 1, "{}", output);
+                let (_, parsed) = records.pop().unwrap();
+                let parsed = parsed.unwrap_or_else(|e| panic!("{}: {}", output, e));
+                assert_eq!(parsed.other_addresses, record.other_addresses, "{}", output);
+                assert_eq!(parsed.regexes, record.regexes, "{}", output);
+                verifier.check(&parsed)
+            })
+            .collect()
+    }
+
+    /// Account, as if it was found by regexes matching the start of every address
+    fn found(mut account: Account) -> Account {
+        let addresses = std::iter::once((account.format, account.address.clone()))
+            .chain(account.other_addresses.iter().cloned());
+        account.regexes = addresses
+            .map(|(format, address)| (format, format!("^{}", regex::escape(&address[..4]))))
+            .collect();
+        account
+    }
+
+    fn assert_valid(account: &Account) {
+        let account = found(account.clone());
+        for verdict in check_printed(&account, None) {
+            assert!(
+                verdict.problems.is_empty(),
+                "{}: {:?}",
+                account,
+                verdict.problems
+            );
+            assert_eq!(verdict.unverified, None, "{}", account);
+            assert!(!verdict.pattern_unchecked, "{}", account);
+        }
+    }
+
+    #[test]
+    fn single_format() {
+        for &scheme in Scheme::ALL {
+            let mut account = Account::from_seed(scheme, AddressFormat::Ss58(42), [7; 32]);
+            assert_valid(&account);
+            // Printed regex may contain field and pair separators, and escapes
+            account.regexes = vec![(AddressFormat::Ss58(42), r"^5[^, ;\\]".to_string())];
+            for verdict in check_printed(&account, None) {
+                assert!(verdict.problems.is_empty(), "{:?}", verdict.problems);
+            }
+        }
+    }
+
+    #[test]
+    fn multi_format() {
+        for &scheme in Scheme::ALL {
+            let mut account = Account::from_seed(scheme, AddressFormat::Ss58(42), [7; 32]);
+            for format in [0, 2, 7391, 16383] {
+                account.address_in(AddressFormat::Ss58(format));
+            }
+            if scheme == Scheme::Ecdsa {
+                account.address_in(AddressFormat::H160);
+            }
+            assert_valid(&account);
+        }
+    }
+
+    #[test]
+    fn h160() {
+        let mut account = Account::from_seed(Scheme::Ecdsa, AddressFormat::H160, [7; 32]);
+        // Address encodes 20 bytes, not substrate account id
+        let record = Record::new(1, &account);
+        assert_eq!(record.account_id, record.address.to_lowercase());
+        assert_valid(&account);
+        account.address_in(AddressFormat::Ss58(42));
+        assert_valid(&account);
+    }
+
+    #[test]
+    fn phrase() {
+        let source = SeedSource::Mnemonic(MnemonicType::Words12, Language::English);
+        for &scheme in Scheme::ALL {
+            let account = Account::generate(&mut thread_rng(), scheme, &source, 42.into());
+            assert!(account.phrase.is_some());
+            assert_valid(&account);
+        }
+    }
+
+    #[test]
+    fn keystore() {
+        for scheme in [Scheme::Sr25519, Scheme::Ed25519] {
+            let account = found(Account {
+                seed: None,
+                ..Account::from_seed(scheme, AddressFormat::Ss58(42), [7; 32])
+            });
+            for verdict in check_printed(&account, Some("keystore/5Fancy.json")) {
+                assert!(verdict.problems.is_empty(), "{:?}", verdict.problems);
+                assert_eq!(verdict.unverified, Some("secret was exported to keystore"));
+                assert!(!verdict.pattern_unchecked);
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_address() {
+        let mut account = Account::from_seed(Scheme::Sr25519, AddressFormat::Ss58(42), [7; 32]);
+        account.address_in(AddressFormat::Ss58(0));
+        let other = Account::from_seed(Scheme::Sr25519, AddressFormat::Ss58(0), [8; 32]);
+        account.other_addresses[0].1 = other.address;
+        for verdict in check_printed(&account, None) {
+            assert_eq!(verdict.problems.len(), 1, "{:?}", verdict.problems);
+        }
+    }
+
+    #[test]
+    fn recorded_regex() {
+        let mut account = Account::from_seed(Scheme::Sr25519, AddressFormat::Ss58(42), [7; 32]);
+        for verdict in check_printed(&account, None) {
+            assert!(verdict.problems.is_empty(), "{:?}", verdict.problems);
+            assert!(verdict.pattern_unchecked);
+        }
+        account.regexes = vec![(AddressFormat::Ss58(42), "^5Fancy".to_string())];
+        for verdict in check_printed(&account, None) {
+            assert_eq!(verdict.problems.len(), 1, "{:?}", verdict.problems);
+        }
+    }
+
+    #[test]
+    fn malformed_public_key() {
+        let account = Account::from_seed(Scheme::Ecdsa, AddressFormat::H160, [7; 32]);
+        let verifier = Verifier::new(Language::English);
+        for public in [
+            "0x1234",
+            "0x02ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        ] {
+            let record = Record {
+                seed: None,
+                public: public.to_string(),
+                ..Record::new(1, &account)
+            };
+            assert_eq!(verifier.check(&record).problems.len(), 1);
+        }
+    }
+}
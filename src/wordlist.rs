@@ -0,0 +1,123 @@
+use crate::{analysis::estimate_words, matcher::ALPHABET, Account, AddressFormat, Error, Estimate};
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use std::cmp::Reverse;
+use std::sync::Arc;
+
+/// Words, which are searched anywhere after leading characters of address
+///
+/// All words are checked at once by Aho-Corasick automaton, which is much faster than
+/// alternation of words in a regex
+#[derive(Clone)]
+pub struct Wordlist {
+    automaton: AhoCorasick,
+    words: Arc<[String]>,
+    skipped: Vec<String>,
+    format: AddressFormat,
+    ignore_case: bool,
+}
+
+impl Wordlist {
+    /// Words, which can't appear in addresses of `format`, are skipped, see [`Wordlist::skipped`]
+    ///
+    /// With `ignore_case`, words match letters in any case
+    pub fn new(
+        words: impl IntoIterator<Item = String>,
+        format: AddressFormat,
+        ignore_case: bool,
+    ) -> Result<Self, Error> {
+        let (words, skipped): (Vec<_>, Vec<_>) = words
+            .into_iter()
+            .filter(|w| !w.is_empty())
+            .partition(|w| w.bytes().all(|c| representable(c, format, ignore_case)));
+        if words.is_empty() {
+            return Err(Error::EmptyWordlist(format));
+        }
+        let automaton = AhoCorasickBuilder::new()
+            // Overlapping matches are reported with standard semantics only
+            .match_kind(MatchKind::Standard)
+            .ascii_case_insensitive(ignore_case)
+            .build(&words)
+            .expect("automaton for wordlist is not too large");
+        Ok(Self {
+            automaton,
+            words: words.into(),
+            skipped,
+            format,
+            ignore_case,
+        })
+    }
+
+    /// Words, which are searched for
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+    /// Words, which contain characters not used by the address format
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+    /// Probability of random address to contain any word
+    pub fn estimate(&self) -> Estimate {
+        estimate_words(&self.words, self.format, self.ignore_case)
+    }
+
+    /// Longest word contained in address, and its character index
+    pub fn find(&self, address: &str) -> Option<(&str, usize)> {
+        let skip = self.format.fixed_chars().min(address.len());
+        let found = self.longest(&address[skip..]).or_else(|| {
+            // Like patterns, h160 words match lowercase form too
+            (self.format == AddressFormat::H160)
+                .then(|| self.longest(&address[skip..].to_lowercase()))
+                .flatten()
+        })?;
+        Some((&self.words[found.0], found.1 + skip))
+    }
+
+    /// The first one of the longest words, which may overlap shorter ones
+    fn longest(&self, haystack: &str) -> Option<(usize, usize)> {
+        self.automaton
+            .find_overlapping_iter(haystack)
+            .max_by_key(|m| (m.len(), Reverse(m.start())))
+            .map(|m| (m.pattern().as_usize(), m.start()))
+    }
+
+    /// Saves found word to [`Account::word`]
+    pub(crate) fn is_match(&self, account: &mut Account) -> bool {
+        let address = account.address_in(self.format);
+        let Some((word, position)) = self.find(address) else {
+            return false;
+        };
+        let found = address[position..position + word.len()].to_string();
+        let word = word.to_string();
+        if self.ignore_case && found != word {
+            account.matched.get_or_insert(found);
+        }
+        account.word = Some((word, position));
+        true
+    }
+}
+
+/// Whether character may appear in addresses of `format`
+fn representable(c: u8, format: AddressFormat, ignore_case: bool) -> bool {
+    match format {
+        // Checksummed addresses have letters in both cases
+        AddressFormat::H160 => c.is_ascii_hexdigit(),
+        AddressFormat::Ss58(_) if ignore_case => {
+            ALPHABET.iter().any(|a| a.eq_ignore_ascii_case(&c))
+        }
+        AddressFormat::Ss58(_) => ALPHABET.contains(&c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_overlapping_word() {
+        let address = "5Fabcdefgh";
+        for words in [["abc", "bcdefg", "gh"], ["gh", "bcdefg", "abc"]] {
+            let wordlist = Wordlist::new(words.map(str::to_string), 42.into(), false).unwrap();
+            assert_eq!(wordlist.find(address), Some(("bcdefg", 3)));
+        }
+    }
+}